@@ -0,0 +1,22 @@
+// Mock search server for demos and manual testing
+//
+// Run with `cargo run --example mock_server --features test-support`, then
+// add the printed section to your config.toml (or a file passed through
+// ST_CONFIG) and start `st` to search against the local server.
+
+use std::time::Duration;
+
+use search_in_terminal::test_support::MockServer;
+
+fn main() {
+    let server = MockServer::start();
+
+    println!("Mock search server listening on http://{}", server.addr());
+    println!();
+    println!("{}", server.config_toml());
+    println!("Press Ctrl+C to stop.");
+
+    loop {
+        std::thread::sleep(Duration::from_secs(3600));
+    }
+}
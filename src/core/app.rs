@@ -1,60 +1,258 @@
 use anyhow::Result;
-use std::time::Instant;
+use crossterm::event::KeyEvent;
+use futures_util::StreamExt;
 
 use ratatui::widgets::ListState;
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
 
 use crate::{
+    core::{
+        editor,
+        history::{History, HistoryEntry},
+        keymap::{Action, Keymap, Mode},
+    },
     search::{
-        engine::SearchEngine,
-        models::{ITEMS_PER_PAGE, RATE_LIMIT_DURATION},
+        engine::{search_stream, CacheMode, PageSource, ResultBatch, SearchEngine, SearchPage},
+        models::ITEMS_PER_PAGE,
+        rate_limit::RATE_LIMITER,
     },
     SearchResult, CONFIG,
 };
 
+/// Side effect requested by a key press, carried out by the event loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Nothing to do beyond redrawing
+    None,
+    /// Exit the application
+    Quit,
+    /// Run a new search for the current input
+    Search,
+    /// Move to the next (positive) or previous (negative) page
+    ChangePage(i32),
+    /// Open the selected result in the browser
+    OpenUrl,
+    /// Search the current input again, bypassing the cache
+    Refresh,
+}
+
+/// What a background search task fetches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    /// The first page of a new search
+    FirstPage,
+    /// The next engine page, appended to the results
+    NextPage,
+    /// A fresh copy of a first page shown from the cache, replacing its
+    /// `replaces` results
+    Refresh { replaces: usize },
+}
+
+/// Search running in a background task
+#[derive(Debug)]
+pub struct SearchTask {
+    /// Tells this task's updates apart from those of superseded tasks
+    pub id: u64,
+    /// What the task fetches
+    pub kind: SearchKind,
+    /// Engines the task searches with
+    pub engines: Vec<SearchEngine>,
+    /// Query the task searches for
+    pub query: String,
+    /// Index of the first result of the pages the task fetches
+    pub start: u16,
+    /// When the task was started
+    pub started: Instant,
+    handle: JoinHandle<()>,
+    /// Batches held back while the task is a prefetch
+    buffered: Vec<ResultBatch>,
+    /// Whether every engine has answered, for prefetches
+    finished: bool,
+    /// Whether any of the task's results are shown yet
+    shown: bool,
+    /// Whether the view moved on to the first page the task added
+    advanced: bool,
+    /// Errors of the engines that failed
+    errors: Vec<String>,
+}
+
+/// Progress of a background search task, sent back to the event loop
+#[derive(Debug)]
+pub enum SearchUpdate {
+    /// One engine's page, as soon as it arrives
+    Batch { id: u64, batch: ResultBatch },
+    /// Every engine has answered
+    Done { id: u64 },
+    /// Autocomplete suggestions of the engine for a partial query
+    Suggestions {
+        query: String,
+        suggestions: Vec<String>,
+    },
+    /// A submitted query could not be saved to the history file
+    HistoryNotSaved { error: String },
+}
+
+/// Where a query suggestion comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionSource {
+    /// An earlier query starting with the input
+    History,
+    /// The engine's autocomplete endpoint
+    Engine,
+}
+
+/// Completion of the input shown in the dropdown under the input box
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Suggested query
+    pub text: String,
+    /// Where the suggestion comes from
+    pub source: SuggestionSource,
+}
+
+/// State of the Ctrl-R history search popup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistorySearch {
+    /// Text the history is filtered by
+    pub pattern: String,
+    /// Byte offset of the cursor in the pattern
+    pub cursor: usize,
+    /// Index of the selected match
+    pub selected: usize,
+}
+
+/// Scroll state of the help overlay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HelpView {
+    /// First line shown, kept in range when drawing
+    pub scroll: usize,
+    /// Lines shown at once, as of the last draw
+    pub page: usize,
+}
+
+/// Frames of the spinner shown while searching
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 /// Application state structure
 #[derive(Debug)]
 pub struct App {
     /// User search query input
     pub input: String,
-    
+
+    /// Byte offset of the cursor in the input, on a character boundary
+    pub cursor: usize,
+
     /// List of search results
     pub search_results: Vec<SearchResult>,
-    
+
     /// Currently selected result index
     pub selected_index: usize,
-    
+
     /// Whether in input mode
     pub input_mode: bool,
-    
+
     /// Error message
     pub error_message: Option<String>,
-    
+
     /// Warning message
     pub warning_message: Option<String>,
-    
+
     /// Current scroll position in the results list
     pub scroll_offset: usize,
-    
+
     /// Current page number (0-based)
     pub page: usize,
-    
+
     /// Total number of available pages
     pub total_pages: usize,
-    
-    /// Timestamp of the last search operation
-    pub last_search: Option<Instant>,
-    
+
     /// Flag indicating whether a search is in progress
     pub is_loading: bool,
-    
+
+    /// Search running in the background, while `is_loading`
+    pub search: Option<SearchTask>,
+
     /// Current search engine being used
     pub search_engine: SearchEngine,
-    
+
+    /// Whether searches query all engines at once instead of `search_engine`
+    pub all_engines: bool,
+
     /// State of the results list selection
     pub list_state: ListState,
-    
+
     /// Starting index for pagination
     pub start: u16,
+
+    /// Whether searches are answered only from the cache
+    pub offline: bool,
+
+    /// Whether any shown results come from the cache
+    pub cached: bool,
+
+    /// Whether any shown results come from expired cache entries
+    pub stale: bool,
+
+    /// Refresh of cached results running in the background
+    pub refresh: Option<SearchTask>,
+
+    /// Next engine page loaded ahead of the user paging to it
+    pub prefetch: Option<SearchTask>,
+
+    /// Whether the last page load found nothing new, so prefetching stops
+    pub exhausted: bool,
+
+    /// Queries submitted in this and earlier sessions
+    pub history: History,
+
+    /// Whether submitted queries are kept out of the history
+    pub incognito: bool,
+
+    /// History search popup, while it is open
+    pub history_search: Option<HistorySearch>,
+
+    /// Key bindings in effect
+    pub keymap: Keymap,
+
+    /// Help overlay, while it is open
+    pub help: Option<HelpView>,
+
+    /// Suggestions for the input, history first
+    pub suggestions: Vec<Suggestion>,
+
+    /// Suggestion selected with Tab or the arrow keys
+    pub selected_suggestion: Option<usize>,
+
+    /// Last suggestions of the engine that still match the input
+    remote_suggestions: Vec<String>,
+
+    /// When to ask the engine for suggestions, once typing pauses
+    suggest_due: Option<Instant>,
+
+    /// Request for engine suggestions running in the background
+    suggest_task: Option<JoinHandle<()>>,
+
+    /// Entry recalled with Up/Down, counted back from the newest
+    history_index: Option<usize>,
+
+    /// Input as it was before recalling history entries
+    history_draft: String,
+
+    /// Id given to the next search task
+    next_task_id: u64,
+
+    /// Sending half of the channel search tasks report back on
+    updates: UnboundedSender<SearchUpdate>,
+
+    /// Receiving half of the channel search tasks report back on
+    update_receiver: UnboundedReceiver<SearchUpdate>,
 }
 
 impl App {
@@ -62,9 +260,11 @@ impl App {
     pub fn new() -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
+        let (updates, update_receiver) = mpsc::unbounded_channel();
 
         Self {
             input: String::new(),
+            cursor: 0,
             search_results: Vec::new(),
             selected_index: 0,
             input_mode: true,
@@ -73,11 +273,33 @@ impl App {
             scroll_offset: 0,
             page: 0,
             total_pages: 0,
-            last_search: None,
             is_loading: false,
+            search: None,
             search_engine: SearchEngine::favor(&CONFIG.engine.favor),
+            all_engines: false,
             list_state,
             start: 0,
+            offline: false,
+            cached: false,
+            stale: false,
+            refresh: None,
+            prefetch: None,
+            exhausted: false,
+            history: History::from_config(&CONFIG.history),
+            incognito: !CONFIG.history.record,
+            history_search: None,
+            keymap: Keymap::from_config(&CONFIG.keys).unwrap_or_default(),
+            help: None,
+            suggestions: Vec::new(),
+            selected_suggestion: None,
+            remote_suggestions: Vec::new(),
+            suggest_due: None,
+            suggest_task: None,
+            history_index: None,
+            history_draft: String::new(),
+            next_task_id: 0,
+            updates,
+            update_receiver,
         }
     }
 
@@ -86,7 +308,7 @@ impl App {
         if self.search_results.is_empty() {
             self.total_pages = 1;
         } else {
-            self.total_pages = (self.search_results.len() + ITEMS_PER_PAGE - 1) / ITEMS_PER_PAGE;
+            self.total_pages = self.search_results.len().div_ceil(ITEMS_PER_PAGE);
         }
     }
 
@@ -105,38 +327,428 @@ impl App {
         (start_index, end_index)
     }
 
+    /// Engines searches are sent to
+    pub fn engines(&self) -> Vec<SearchEngine> {
+        if self.all_engines {
+            SearchEngine::all()
+        } else {
+            vec![self.search_engine]
+        }
+    }
+
+    /// How searches may use the cache
+    pub fn cache_mode(&self) -> CacheMode {
+        if self.offline {
+            CacheMode::Offline
+        } else {
+            CacheMode::Normal
+        }
+    }
+
+    /// Applies a key press to the application state
+    ///
+    /// The key is looked up in the keymap, first among the global bindings
+    /// and then in the mode on top: the help overlay, the history search
+    /// popup, the suggestion dropdown, the input or the results. State
+    /// changes that need no I/O happen here; anything else is returned as a
+    /// [`Command`] for the event loop to perform.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Command {
+        if let Some(help) = self.help.as_mut() {
+            match self.keymap.action(Mode::Help, key) {
+                Some(Action::ScrollDown) => help.scroll = help.scroll.saturating_add(1),
+                Some(Action::ScrollUp) => help.scroll = help.scroll.saturating_sub(1),
+                Some(Action::PageDown) => help.scroll = help.scroll.saturating_add(help.page),
+                Some(Action::PageUp) => help.scroll = help.scroll.saturating_sub(help.page),
+                Some(Action::ScrollToTop) => help.scroll = 0,
+                Some(Action::ScrollToBottom) => help.scroll = usize::MAX,
+                Some(Action::Close) => self.help = None,
+                _ => {
+                    if self.keymap.action(Mode::Global, key) == Some(Action::Cancel) {
+                        self.cancel_search();
+                    }
+                }
+            }
+            return Command::None;
+        }
+
+        match self.keymap.action(Mode::Global, key) {
+            Some(Action::Cancel) => {
+                self.cancel_search();
+                self.history_search = None;
+                return Command::None;
+            }
+            Some(Action::ShowHelp) => {
+                self.show_help();
+                return Command::None;
+            }
+            _ => {}
+        }
+
+        if self.history_search.is_some() {
+            return self.handle_history_search_key(key);
+        }
+
+        if self.input_mode {
+            if let Some(command) = self.handle_suggestion_key(key) {
+                return command;
+            }
+            match self.keymap.action(Mode::Input, key) {
+                Some(Action::StopEditing) => {
+                    self.dismiss_suggestions();
+                    self.input_mode = false;
+                }
+                Some(Action::Submit) => {
+                    self.dismiss_suggestions();
+                    self.record_history();
+                    return Command::Search;
+                }
+                Some(Action::HistoryOlder) => self.recall_history(true),
+                Some(Action::HistoryNewer) => self.recall_history(false),
+                Some(Action::SearchHistory) => {
+                    self.dismiss_suggestions();
+                    self.history_search = Some(HistorySearch::default());
+                }
+                Some(Action::ShowHelp) => self.show_help(),
+                _ => {
+                    let before = self.input.clone();
+                    editor::handle_key(&mut self.input, &mut self.cursor, key, &self.keymap);
+                    // Editing a recalled entry makes it the new input
+                    if self.input != before {
+                        self.history_index = None;
+                        self.update_suggestions();
+                    }
+                }
+            }
+            return Command::None;
+        }
+
+        let Some(action) = self.keymap.action(Mode::Results, key) else {
+            return Command::None;
+        };
+        match action {
+            Action::CancelSearch => self.cancel_search(),
+            Action::Quit => return Command::Quit,
+            Action::EditInput => {
+                self.input_mode = true;
+                self.cursor = self.input.len();
+            }
+            Action::ClearResults => self.clear_results(),
+            Action::ToggleOffline => self.offline = !self.offline,
+            Action::ToggleIncognito => {
+                self.incognito = !self.incognito;
+                if self.incognito {
+                    // Don't send a prefix typed before the switch
+                    self.suggest_due = None;
+                    if let Some(task) = self.suggest_task.take() {
+                        task.abort();
+                    }
+                }
+            }
+            Action::Refresh if !self.input.is_empty() => return Command::Refresh,
+            Action::NextEngine => {
+                if self.all_engines {
+                    self.all_engines = false;
+                } else {
+                    self.search_engine = self.search_engine.next();
+                }
+                if !self.input.is_empty() {
+                    return Command::Search;
+                }
+            }
+            Action::ToggleAllEngines => {
+                self.all_engines = !self.all_engines;
+                if !self.input.is_empty() {
+                    return Command::Search;
+                }
+            }
+            Action::OpenUrl => return Command::OpenUrl,
+            Action::SelectNext => {
+                let i = match self.list_state.selected() {
+                    Some(i) => i.saturating_add(1),
+                    None => 0,
+                };
+                self.list_state.select(Some(i));
+            }
+            Action::SelectPrevious => {
+                let i = match self.list_state.selected() {
+                    Some(i) => i.saturating_sub(1),
+                    None => 0,
+                };
+                self.list_state.select(Some(i));
+            }
+            Action::PreviousPage => return Command::ChangePage(-1),
+            Action::NextPage => return Command::ChangePage(1),
+            Action::ShowHelp => self.show_help(),
+            _ => {}
+        }
+
+        Command::None
+    }
+
+    /// Opens the help overlay at the top
+    pub fn show_help(&mut self) {
+        self.help = Some(HelpView::default());
+    }
+
+    /// Applies a key press to the open history search popup
+    ///
+    /// Accepting searches for the selected query right away, accepting for
+    /// editing only copies it into the input. Keys the popup doesn't bind
+    /// edit the pattern.
+    fn handle_history_search_key(&mut self, key: KeyEvent) -> Command {
+        let Some(search) = self.history_search.as_mut() else {
+            return Command::None;
+        };
+        let last = self.history.search(&search.pattern).len().saturating_sub(1);
+
+        match self.keymap.action(Mode::HistorySearch, key) {
+            Some(Action::Close) => self.history_search = None,
+            Some(action @ (Action::Accept | Action::AcceptForEditing)) => {
+                let selected = self
+                    .history
+                    .search(&search.pattern)
+                    .get(search.selected)
+                    .map(|entry| entry.query.clone());
+                self.history_search = None;
+                if let Some(query) = selected {
+                    self.input = query;
+                    self.cursor = self.input.len();
+                    self.history_index = None;
+                    if action == Action::Accept {
+                        self.record_history();
+                        return Command::Search;
+                    }
+                }
+            }
+            Some(Action::SelectPrevious) => search.selected = search.selected.saturating_sub(1),
+            Some(Action::SelectNext) => search.selected = (search.selected + 1).min(last),
+            _ => {
+                let before = search.pattern.clone();
+                editor::handle_key(&mut search.pattern, &mut search.cursor, key, &self.keymap);
+                if search.pattern != before {
+                    search.selected = 0;
+                }
+            }
+        }
+        Command::None
+    }
+
+    /// Applies a key press to the suggestion dropdown, if it is shown and
+    /// the key is one it handles
+    ///
+    /// Selecting the next suggestion moves into the list and down it,
+    /// selecting the previous one moves back up and out of it. Accepting
+    /// searches for the selected suggestion and closing hides the list. Keys
+    /// that do nothing here, like accepting with nothing selected, fall
+    /// through to the input.
+    fn handle_suggestion_key(&mut self, key: KeyEvent) -> Option<Command> {
+        if self.suggestions.is_empty() {
+            return None;
+        }
+        let last = self.suggestions.len() - 1;
+
+        match (
+            self.keymap.action(Mode::Suggestions, key)?,
+            self.selected_suggestion,
+        ) {
+            (Action::SelectNext, None) => self.selected_suggestion = Some(0),
+            (Action::SelectNext, Some(index)) => {
+                self.selected_suggestion = Some((index + 1).min(last));
+            }
+            (Action::SelectPrevious, Some(index)) => {
+                self.selected_suggestion = index.checked_sub(1);
+            }
+            (Action::Accept, Some(index)) => {
+                self.input = self.suggestions[index].text.clone();
+                self.cursor = self.input.len();
+                self.dismiss_suggestions();
+                self.record_history();
+                return Some(Command::Search);
+            }
+            (Action::Close, _) => self.dismiss_suggestions(),
+            _ => return None,
+        }
+        Some(Command::None)
+    }
+
+    /// Recomputes the suggestions after the input changed
+    ///
+    /// History suggestions are found right away, and the engine's last
+    /// suggestions are kept while they still match. The engine is asked again
+    /// once typing pauses for `[suggest] debounce` milliseconds, unless
+    /// offline or incognito, which keeps what is typed on this machine.
+    pub fn update_suggestions(&mut self) {
+        self.selected_suggestion = None;
+        if let Some(task) = self.suggest_task.take() {
+            task.abort();
+        }
+        let input = self.input.trim_start().to_lowercase();
+        if !CONFIG.suggest.enabled || input.trim().is_empty() {
+            self.dismiss_suggestions();
+            return;
+        }
+
+        self.remote_suggestions
+            .retain(|suggestion| suggestion.to_lowercase().starts_with(&input));
+        self.merge_suggestions();
+        if CONFIG.suggest.remote && !self.offline && !self.incognito {
+            self.suggest_due =
+                Some(Instant::now() + Duration::from_millis(CONFIG.suggest.debounce));
+        }
+    }
+
+    /// Hides the suggestions and drops any pending request for them
+    pub fn dismiss_suggestions(&mut self) {
+        self.suggestions.clear();
+        self.selected_suggestion = None;
+        self.remote_suggestions.clear();
+        self.suggest_due = None;
+        if let Some(task) = self.suggest_task.take() {
+            task.abort();
+        }
+    }
+
+    /// Lists earlier queries extending the input, newest first, followed by
+    /// the engine's suggestions, each query once
+    fn merge_suggestions(&mut self) {
+        let input = self.input.trim_start().to_lowercase();
+        let mut seen = HashSet::from([input.trim_end().to_string()]);
+        let history = self
+            .history
+            .entries()
+            .iter()
+            .rev()
+            .filter(|entry| entry.query.to_lowercase().starts_with(&input))
+            .map(|entry| (entry.query.as_str(), SuggestionSource::History));
+        let remote = self
+            .remote_suggestions
+            .iter()
+            .map(|text| (text.as_str(), SuggestionSource::Engine));
+
+        let selected = self
+            .selected_suggestion
+            .map(|index| self.suggestions[index].text.clone());
+        self.suggestions = history
+            .chain(remote)
+            .filter(|(text, _)| seen.insert(text.to_lowercase()))
+            .take(CONFIG.suggest.max_items)
+            .map(|(text, source)| Suggestion {
+                text: text.to_string(),
+                source,
+            })
+            .collect();
+        // Keep the selection on the same suggestion if it is still listed
+        self.selected_suggestion = selected.and_then(|selected| {
+            self.suggestions
+                .iter()
+                .position(|suggestion| suggestion.text == selected)
+        });
+    }
+
+    /// Asks the engine for suggestions for the input in the background
+    fn spawn_suggest(&mut self) {
+        let engine = self.search_engine;
+        let query = self.input.clone();
+        let updates = self.updates.clone();
+        self.suggest_task = Some(tokio::spawn(async move {
+            // Suggestions are a convenience; without them the history ones stay
+            let suggestions = engine.suggest(&query).await.unwrap_or_default();
+            let _ = updates.send(SearchUpdate::Suggestions { query, suggestions });
+        }));
+    }
+
+    /// Entries matching the history search pattern, best match first
+    pub fn history_matches(&self) -> Vec<&HistoryEntry> {
+        self.history_search
+            .as_ref()
+            .map(|search| self.history.search(&search.pattern))
+            .unwrap_or_default()
+    }
+
+    /// Replaces the input with an older (or newer) history entry
+    ///
+    /// Going newer than the newest entry restores what was typed before.
+    fn recall_history(&mut self, older: bool) {
+        self.dismiss_suggestions();
+        let index = match (self.history_index, older) {
+            (None, true) => 0,
+            (Some(index), true) => index + 1,
+            (None, false) => return,
+            (Some(0), false) => {
+                self.input = std::mem::take(&mut self.history_draft);
+                self.cursor = self.input.len();
+                self.history_index = None;
+                return;
+            }
+            (Some(index), false) => index - 1,
+        };
+        let Some(entry) = self.history.get(index) else {
+            return;
+        };
+
+        if self.history_index.is_none() {
+            self.history_draft = std::mem::take(&mut self.input);
+        }
+        self.input = entry.query.clone();
+        self.cursor = self.input.len();
+        self.history_index = Some(index);
+    }
+
+    /// Adds the input to the history unless in incognito mode
+    ///
+    /// The entry is available right away; the file is updated on the
+    /// blocking thread pool, and a failure comes back as a warning.
+    fn record_history(&mut self) {
+        self.history_index = None;
+        if self.incognito {
+            return;
+        }
+        let engine = if self.all_engines {
+            "All"
+        } else {
+            self.search_engine.as_str()
+        };
+        let Some(entry) = self.history.push(&self.input, engine) else {
+            return;
+        };
+        let file = self.history.file().clone();
+        let saving = tokio::task::spawn_blocking(move || file.append(entry));
+        let updates = self.updates.clone();
+        tokio::spawn(async move {
+            if let Ok(Err(e)) = saving.await {
+                let _ = updates.send(SearchUpdate::HistoryNotSaved {
+                    error: e.to_string(),
+                });
+            }
+        });
+    }
+
     /// Switches to the next or previous page
-    pub async fn change_page(&mut self, direction: i32) -> Result<()> {
+    ///
+    /// Moving past the last page loads the next engine page in the background;
+    /// the view moves on when it arrives.
+    pub fn change_page(&mut self, direction: i32) {
         if self.search_results.is_empty() {
-            return Ok(());
+            return;
         }
 
         if let Some(new_page) = (self.page as i32).checked_add(direction) {
             if new_page >= 0 && new_page < self.total_pages as i32 {
                 self.page = new_page as usize;
-                let (start_index, _) = self.current_page_range();
-                self.selected_index = start_index;
-                self.scroll_offset = 0;
-                self.list_state.select(Some(0));
+                self.reset_selection();
+                self.start_prefetch();
             } else if new_page >= 0 && direction > 0 {
                 // If attempting to go to the next page but already on the last page, try to get more results
-                self.next_search().await?;
-                if self.page < self.total_pages - 1 {
-                    self.page += 1;
-                    let (start_index, _) = self.current_page_range();
-                    self.selected_index = start_index;
-                    self.scroll_offset = 0;
-                    self.list_state.select(Some(0));
-                }
+                self.load_next_page();
             }
         }
-
-        Ok(())
     }
 
     /// Clears the input field
     pub fn clear_input(&mut self) {
         self.input.clear();
+        self.cursor = 0;
+        self.dismiss_suggestions();
     }
 
     /// Clears the search results
@@ -149,103 +761,451 @@ impl App {
         self.list_state.select(Some(0));
         self.error_message = None;
         self.warning_message = None;
-        self.input.clear();
+        self.clear_input();
         self.input_mode = true;
         self.start = 0;
+        self.cached = false;
+        self.stale = false;
+        self.cancel_refresh();
+        self.cancel_prefetch();
     }
 
-    pub async fn next_search(&mut self) -> Result<()> {
+    /// Starts a search for the current input in the background
+    pub fn start_search(&mut self, mode: CacheMode) {
         if self.input.is_empty() {
-            return Ok(());
+            return;
         }
 
+        // A new search supersedes the running one, any refresh and prefetch
+        if let Some(task) = self.search.take() {
+            task.handle.abort();
+        }
+        self.cancel_refresh();
+        self.cancel_prefetch();
+        self.exhausted = false;
         self.error_message = None;
         self.warning_message = None;
+        self.start = 0; // Reset the start position
+        let query = self.input.clone();
+        self.search =
+            Some(self.spawn_search(SearchKind::FirstPage, self.engines(), query, 0, mode));
+        self.is_loading = true;
+        self.input_mode = false;
+    }
 
-        // Update the start position
-        self.start = self.start.saturating_add(10);
+    /// Starts the current search again in the background, bypassing the cache
+    pub fn start_refresh(&mut self) {
+        if self.offline {
+            self.warning_message = Some("Cannot refresh results in offline mode".to_string());
+            return;
+        }
+        self.start_search(CacheMode::Refresh);
+    }
 
-        // Perform the search using the selected search engine
-        let results = match self.search_engine.search(&self.input, self.start).await {
-            Ok(results) => Ok(results),
-            Err(e) => {
-                self.error_message = Some(format!("Next search failed: {}", e));
-                Err(e)
+    /// Performs a search operation and waits for its results
+    pub async fn perform_search(&mut self) -> Result<()> {
+        self.start_search(self.cache_mode());
+        self.wait_for_search().await;
+        Ok(())
+    }
+
+    /// Performs the current search again, bypassing the cache, and waits for
+    /// its results
+    pub async fn refresh_search(&mut self) -> Result<()> {
+        self.start_refresh();
+        self.wait_for_search().await;
+        Ok(())
+    }
+
+    /// Loads the next engine page in the background, unless a search is running
+    ///
+    /// A prefetch of the page is taken over, applying what it already has.
+    fn load_next_page(&mut self) {
+        if self.input.is_empty() || self.search.is_some() {
+            return;
+        }
+
+        self.error_message = None;
+        self.warning_message = None;
+
+        // Update the start position
+        let start = self.start.saturating_add(10);
+        let engines = self.engines();
+        let mut task = match self.prefetch.take() {
+            Some(task)
+                if task.start == start
+                    && task.engines == engines
+                    && task.query == self.input
+                    // Failed prefetches are tried again
+                    && !(task.finished && task.buffered.iter().all(|b| b.page.is_err())) =>
+            {
+                task
+            }
+            prefetch => {
+                if let Some(prefetch) = prefetch {
+                    prefetch.handle.abort();
+                }
+                let query = self.input.clone();
+                self.spawn_search(
+                    SearchKind::NextPage,
+                    engines,
+                    query,
+                    start,
+                    self.cache_mode(),
+                )
             }
         };
+        self.start = start;
+        self.is_loading = true;
 
-        match results {
-            Ok(mut results) => {
-                // Check for duplicate results
-                let existing_urls: std::collections::HashSet<_> =
-                    self.search_results.iter().map(|r| &r.url).collect();
+        for batch in std::mem::take(&mut task.buffered) {
+            self.append_next_page(&mut task, batch);
+        }
+        if task.finished {
+            self.is_loading = false;
+            self.finish_search(task);
+        } else {
+            self.search = Some(task);
+        }
+    }
 
-                // Only add non-duplicate results
-                results.retain(|result| !existing_urls.contains(&result.url));
+    /// Loads the page after the last one ahead of time, when the last page
+    /// is shown and every engine has rate-limit tokens to spare
+    fn start_prefetch(&mut self) {
+        if !CONFIG.search.prefetch
+            || self.offline
+            || self.exhausted
+            || self.input.is_empty()
+            || self.search.is_some()
+            || self.prefetch.is_some()
+            || self.search_results.is_empty()
+            || self.page + 1 < self.total_pages
+        {
+            return;
+        }
+        let engines = self.engines();
+        if !engines
+            .iter()
+            .all(|engine| RATE_LIMITER.has_spare(engine.as_str()))
+        {
+            return;
+        }
 
-                if !results.is_empty() {
-                    self.search_results.extend(results);
-                    self.total_pages();
-                } else {
-                    self.warning_message = Some("No more results found".to_string());
+        let query = self.input.clone();
+        let start = self.start.saturating_add(10);
+        self.prefetch = Some(self.spawn_search(
+            SearchKind::NextPage,
+            engines,
+            query,
+            start,
+            self.cache_mode(),
+        ));
+    }
+
+    /// Stops a prefetch that no longer matches the search
+    fn cancel_prefetch(&mut self) {
+        if let Some(prefetch) = self.prefetch.take() {
+            prefetch.handle.abort();
+        }
+    }
+
+    /// Cancels the running search, dropping its request and any backoff
+    /// before a retry
+    pub fn cancel_search(&mut self) {
+        let Some(task) = self.search.take() else {
+            return;
+        };
+        task.handle.abort();
+        self.is_loading = false;
+        if task.kind == SearchKind::NextPage {
+            self.start = self.start.saturating_sub(10);
+        }
+        self.warning_message = Some("Search cancelled".to_string());
+    }
+
+    /// Stops a background refresh whose results are no longer wanted
+    fn cancel_refresh(&mut self) {
+        if let Some(refresh) = self.refresh.take() {
+            refresh.handle.abort();
+        }
+    }
+
+    /// Spawns a task streaming the engines' pages, which reports back with
+    /// [`SearchUpdate`]s
+    fn spawn_search(
+        &mut self,
+        kind: SearchKind,
+        engines: Vec<SearchEngine>,
+        query: String,
+        start: u16,
+        mode: CacheMode,
+    ) -> SearchTask {
+        self.next_task_id += 1;
+        let id = self.next_task_id;
+        let updates = self.updates.clone();
+        let mut batches = search_stream(engines.clone(), &query, start, mode);
+
+        let handle = tokio::spawn(async move {
+            while let Some(batch) = batches.next().await {
+                // Nobody is listening once the app has quit
+                if updates.send(SearchUpdate::Batch { id, batch }).is_err() {
+                    return;
                 }
             }
-            Err(e) => {
-                self.error_message = Some(format!("Next search failed: {}", e));
-            }
+            let _ = updates.send(SearchUpdate::Done { id });
+        });
+
+        SearchTask {
+            id,
+            kind,
+            engines,
+            query,
+            start,
+            started: Instant::now(),
+            handle,
+            buffered: Vec::new(),
+            finished: false,
+            shown: false,
+            advanced: false,
+            errors: Vec::new(),
         }
+    }
 
-        Ok(())
+    /// Applies the updates search tasks have sent so far, returning whether
+    /// there were any
+    pub fn poll_updates(&mut self) -> bool {
+        if self.suggest_due.is_some_and(|due| Instant::now() >= due) {
+            self.suggest_due = None;
+            self.spawn_suggest();
+        }
+
+        let mut updated = false;
+        while let Ok(update) = self.update_receiver.try_recv() {
+            self.apply_update(update);
+            updated = true;
+        }
+        updated
     }
 
-    /// Performs a search operation
-    pub async fn perform_search(&mut self) -> Result<()> {
-        if self.input.is_empty() {
-            return Ok(());
+    /// Waits for the next update from a search task and applies it
+    pub async fn next_update(&mut self) {
+        if let Some(update) = self.update_receiver.recv().await {
+            self.apply_update(update);
         }
+    }
 
-        // Check the search rate limit
-        if let Some(last_search) = self.last_search {
-            if last_search.elapsed() < RATE_LIMIT_DURATION {
-                self.warning_message =
-                    Some("Please wait a moment before searching again".to_string());
-                return Ok(());
-            }
+    /// Waits until the running search, if any, has finished
+    pub async fn wait_for_search(&mut self) {
+        while self.search.is_some() {
+            self.next_update().await;
         }
+    }
 
-        self.is_loading = true;
-        self.error_message = None;
-        self.warning_message = None;
-        self.start = 0; // Reset the start position
+    /// Applies the progress of a search task
+    ///
+    /// Updates from superseded or cancelled tasks are dropped; their pages
+    /// are in the cache regardless.
+    pub fn apply_update(&mut self, update: SearchUpdate) {
+        match update {
+            SearchUpdate::Batch { id, batch } => {
+                if let Some(mut task) = self.search.take_if(|task| task.id == id) {
+                    match task.kind {
+                        SearchKind::NextPage => self.append_next_page(&mut task, batch),
+                        _ => self.show_first_page(&mut task, batch),
+                    }
+                    self.search = Some(task);
+                } else if let Some(task) = self.refresh.take_if(|task| task.id == id) {
+                    if let SearchKind::Refresh { replaces } = task.kind {
+                        self.swap_refreshed_page(batch.page, replaces);
+                    }
+                } else if let Some(task) = self.prefetch.as_mut().filter(|task| task.id == id) {
+                    task.buffered.push(batch);
+                }
+            }
+            SearchUpdate::Done { id } => {
+                if let Some(task) = self.search.take_if(|task| task.id == id) {
+                    self.is_loading = false;
+                    self.finish_search(task);
+                } else if let Some(task) = self.prefetch.as_mut().filter(|task| task.id == id) {
+                    task.finished = true;
+                }
+            }
+            SearchUpdate::Suggestions { query, suggestions } => {
+                // Suggestions for an earlier input, or after dismissing, are late
+                if self.suggest_task.is_some() && query == self.input {
+                    self.suggest_task = None;
+                    self.remote_suggestions = suggestions;
+                    self.merge_suggestions();
+                }
+            }
+            SearchUpdate::HistoryNotSaved { error } => {
+                self.warning_message = Some(format!("Failed to save history: {}", error));
+            }
+        }
+    }
 
-        // Perform the search using the selected search engine
-        let results = match self.search_engine.search(&self.input, self.start).await {
-            Ok(results) => Ok(results),
+    /// Shows a page of a new search, replacing the previous results with the
+    /// first page that arrives
+    fn show_first_page(&mut self, task: &mut SearchTask, batch: ResultBatch) {
+        let page = match batch.page {
+            Ok(page) => page,
             Err(e) => {
-                self.error_message = Some(format!("Search failed: {}", e));
-                Err(e)
+                task.errors.push(engine_error(task, batch.engine, e));
+                return;
             }
         };
 
-        match results {
-            Ok(results) => {
-                self.search_results = results;
-                self.selected_index = 0;
-                self.page = 0;
-                self.scroll_offset = 0;
-                self.list_state.select(Some(0));
-                self.total_pages();
+        if page.revalidate {
+            if task.engines.len() == 1 {
+                self.revalidate(batch.engine, task.query.clone(), page.results.len());
+            } else {
+                // Results of several engines are interleaved, so the fresh
+                // page is only kept for the next search
+                refresh_in_background(batch.engine, task.query.clone(), batch.start);
             }
+        }
+
+        let first = !task.shown;
+        if first {
+            task.shown = true;
+            self.search_results.clear();
+            self.cached = false;
+            self.stale = false;
+            self.page = 0;
+        }
+        self.add_results(page);
+        self.total_pages();
+        if first {
+            self.reset_selection();
+        }
+    }
+
+    /// Appends a page loaded past the last one, moving the view on to the
+    /// first new page if it was asked for from the last one
+    fn append_next_page(&mut self, task: &mut SearchTask, batch: ResultBatch) {
+        let page = match batch.page {
+            Ok(page) => page,
             Err(e) => {
-                self.error_message = Some(format!("Search failed: {}", e));
+                task.errors.push(engine_error(task, batch.engine, e));
+                return;
+            }
+        };
+
+        if page.revalidate {
+            // Only the first page is swapped in when refreshed; later
+            // ones are refreshed for the next time they are shown
+            refresh_in_background(batch.engine, task.query.clone(), batch.start);
+        }
+
+        let was_on_last_page = self.page + 1 >= self.total_pages;
+        if self.add_results(page) {
+            task.shown = true;
+            self.total_pages();
+            if !task.advanced && was_on_last_page && self.page < self.total_pages - 1 {
+                task.advanced = true;
+                self.page += 1;
+                self.reset_selection();
+            }
+        }
+    }
+
+    /// Appends the results of a page that aren't shown yet, returning whether
+    /// there were any
+    fn add_results(&mut self, page: SearchPage) -> bool {
+        let mut results = page.results;
+        // Check for duplicate results
+        let existing_urls: std::collections::HashSet<_> =
+            self.search_results.iter().map(|r| &r.url).collect();
+
+        // Only add non-duplicate results
+        results.retain(|result| !existing_urls.contains(&result.url));
+        if results.is_empty() {
+            return false;
+        }
+
+        self.search_results.extend(results);
+        self.cached |= page.source != PageSource::Network;
+        self.stale |= page.source == PageSource::Stale;
+        true
+    }
+
+    /// Reports the engines that failed once every engine has answered
+    fn finish_search(&mut self, task: SearchTask) {
+        let errors = task.errors.join("; ");
+        match (task.kind, task.shown) {
+            (SearchKind::NextPage, false) if errors.is_empty() => {
+                self.exhausted = true;
+                self.warning_message = Some("No more results found".to_string());
+            }
+            (SearchKind::NextPage, false) => {
+                self.error_message = Some(format!("Next search failed: {}", errors));
+            }
+            (_, false) => {
                 self.clear_results();
+                self.input_mode = false;
+                self.error_message = Some(format!("Search failed: {}", errors));
+            }
+            (_, true) if !errors.is_empty() => {
+                self.warning_message = Some(format!("Some engines failed: {}", errors));
             }
+            (_, true) => {}
         }
+        self.start_prefetch();
+    }
 
-        self.is_loading = false;
-        self.input_mode = false;
-        self.last_search = Some(Instant::now());
-        Ok(())
+    /// Refreshes the first page of the current search in the background
+    fn revalidate(&mut self, engine: SearchEngine, query: String, replaces: usize) {
+        self.refresh = Some(self.spawn_search(
+            SearchKind::Refresh { replaces },
+            vec![engine],
+            query,
+            0,
+            CacheMode::Refresh,
+        ));
+    }
+
+    fn swap_refreshed_page(&mut self, page: Result<SearchPage>, replaces: usize) {
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => {
+                self.warning_message = Some(format!("Refresh failed: {}", e));
+                return;
+            }
+        };
+
+        // Later pages were deduplicated against the old first page
+        let urls: std::collections::HashSet<_> =
+            page.results.iter().map(|r| r.url.clone()).collect();
+        let replaces = replaces.min(self.search_results.len());
+        let rest: Vec<_> = self
+            .search_results
+            .drain(replaces..)
+            .filter(|result| !urls.contains(&result.url))
+            .collect();
+        self.search_results = page.results;
+        self.search_results.extend(rest);
+        if self.start == 0 {
+            self.cached = false;
+            self.stale = false;
+        }
+        self.total_pages();
+        self.page = self.page.min(self.total_pages - 1);
+    }
+
+    /// Selects the first result of the current page
+    fn reset_selection(&mut self) {
+        let (start_index, _) = self.current_page_range();
+        self.selected_index = start_index;
+        self.scroll_offset = 0;
+        self.list_state.select(Some(0));
+    }
+
+    /// Spinner frame for the running search
+    pub fn spinner(&self) -> &'static str {
+        let frame = self.search.as_ref().map_or(0, |task| {
+            task.started.elapsed().as_millis() / 100 % SPINNER.len() as u128
+        });
+        SPINNER[frame as usize]
     }
 
     /// Opens the selected URL
@@ -260,6 +1220,20 @@ impl App {
     }
 }
 
+/// Describe an engine's error, naming the engine if the search used several
+fn engine_error(task: &SearchTask, engine: SearchEngine, e: anyhow::Error) -> String {
+    if task.engines.len() > 1 {
+        format!("{}: {}", engine.as_str(), e)
+    } else {
+        e.to_string()
+    }
+}
+
+/// Fetch a page again for the cache, without showing the result
+fn refresh_in_background(engine: SearchEngine, query: String, start: u16) {
+    tokio::spawn(async move { engine.search_page(&query, start, CacheMode::Refresh).await });
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,262 @@
+use anyhow::{anyhow, Result};
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use crate::{
+    search::{
+        cache::{CacheFilter, DiskCache},
+        transport::clear_cookies,
+    },
+    CONFIG,
+};
+
+/// Usage of the management subcommands
+pub const USAGE: &str = "Usage: st [--offline] [--incognito] [QUERY] | st cookies clear | \
+st cache stats|list|purge [--engine NAME] [--query TEXT] [--older-than DURATION]";
+
+/// What to do for the given command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    /// Start the terminal UI with `query` in the input box, answering only
+    /// from the cache if `offline` and keeping queries out of the history if
+    /// `incognito`
+    Tui {
+        offline: bool,
+        incognito: bool,
+        query: Option<String>,
+    },
+    /// Delete the saved cookies of all engines
+    ClearCookies,
+    /// Show the size and hit rate of the disk cache
+    CacheStats(CacheFilter),
+    /// List the disk cache entries
+    CacheList(CacheFilter),
+    /// Remove disk cache entries
+    CachePurge(CacheFilter),
+}
+
+impl CliCommand {
+    /// Parse the command line arguments, without the program name
+    pub fn parse<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args: Vec<S> = args.into_iter().collect();
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        match args.as_slice() {
+            ["cookies", "clear"] => Ok(CliCommand::ClearCookies),
+            ["cookies", ..] => Err(anyhow!("Unknown cookies command\n{}", USAGE)),
+            ["cache", command, options @ ..] => {
+                let filter = parse_filter(options)?;
+                match *command {
+                    "stats" => Ok(CliCommand::CacheStats(filter)),
+                    "list" => Ok(CliCommand::CacheList(filter)),
+                    "purge" => Ok(CliCommand::CachePurge(filter)),
+                    _ => Err(anyhow!("Unknown cache command\n{}", USAGE)),
+                }
+            }
+            ["cache"] => Err(anyhow!("Missing cache command\n{}", USAGE)),
+            flags => parse_tui_flags(flags),
+        }
+    }
+
+    /// Run a command that doesn't need the terminal UI, returning the
+    /// message to print
+    pub fn execute(&self) -> Result<String> {
+        match self {
+            CliCommand::Tui { .. } => Ok(String::new()),
+            CliCommand::ClearCookies => {
+                let removed = clear_cookies(&CONFIG.cookies)?;
+                Ok(format!(
+                    "Removed {} cookie file(s) from {}",
+                    removed,
+                    CONFIG.cookies.dir.display()
+                ))
+            }
+            CliCommand::CacheStats(filter) => cache_stats(&disk_cache(), filter),
+            CliCommand::CacheList(filter) => cache_list(&disk_cache(), filter),
+            CliCommand::CachePurge(filter) => {
+                let cache = disk_cache();
+                let removed = cache.purge(filter)?;
+                Ok(format!(
+                    "Removed {} cache entr{} from {}",
+                    removed,
+                    if removed == 1 { "y" } else { "ies" },
+                    CONFIG.cache.disk_dir.display()
+                ))
+            }
+        }
+    }
+}
+
+/// The configured disk cache, also when `[cache] disk` is off, so entries
+/// left from earlier sessions can still be inspected
+fn disk_cache() -> DiskCache {
+    DiskCache::new(
+        CONFIG.cache.disk_dir.clone(),
+        CONFIG.cache.disk_max_bytes,
+        Duration::from_secs(CONFIG.cache.time_to_live),
+    )
+}
+
+fn parse_tui_flags(flags: &[&str]) -> Result<CliCommand> {
+    let (mut offline, mut incognito, mut query) = (false, false, None);
+    for flag in flags {
+        match *flag {
+            "--offline" => offline = true,
+            "--incognito" => incognito = true,
+            _ if flag.starts_with('-') => {
+                return Err(anyhow!("Unknown option {}\n{}", flag, USAGE))
+            }
+            _ if query.is_none() => query = Some(flag.to_string()),
+            _ => return Err(anyhow!("Unexpected argument {}\n{}", flag, USAGE)),
+        }
+    }
+    Ok(CliCommand::Tui {
+        offline,
+        incognito,
+        query,
+    })
+}
+
+fn parse_filter(options: &[&str]) -> Result<CacheFilter> {
+    let mut filter = CacheFilter::default();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .map(|value| value.to_string())
+                .ok_or_else(|| anyhow!("{} needs a value\n{}", option, USAGE))
+        };
+        match *option {
+            "--engine" => filter.engine = Some(value()?),
+            "--query" => filter.query = Some(value()?),
+            "--older-than" => filter.older_than = Some(parse_duration(&value()?)?),
+            _ => return Err(anyhow!("Unknown option {}\n{}", option, USAGE)),
+        }
+    }
+    Ok(filter)
+}
+
+/// Parse a duration such as `90`, `90s`, `15m`, `12h` or `7d`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let (number, unit) = text.split_at(
+        text.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len()),
+    );
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid duration '{}', expected e.g. 30m or 7d", text))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(anyhow!(
+                "Invalid duration unit in '{}', use s, m, h or d",
+                text
+            ))
+        }
+    };
+    Ok(Duration::from_secs(number.saturating_mul(seconds)))
+}
+
+fn cache_stats(cache: &DiskCache, filter: &CacheFilter) -> Result<String> {
+    let entries = cache.list(filter)?;
+    let mut engines = BTreeMap::new();
+    for entry in &entries {
+        *engines.entry(entry.key.engine.as_str()).or_insert(0) += 1;
+    }
+    let size: u64 = entries.iter().map(|entry| entry.size).sum();
+    let stats = cache.stats();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Directory: {}{}",
+        CONFIG.cache.disk_dir.display(),
+        if CONFIG.cache.disk {
+            ""
+        } else {
+            " (disk cache disabled)"
+        }
+    )?;
+    write!(out, "Entries:   {}", entries.len())?;
+    if !engines.is_empty() {
+        let engines: Vec<_> = engines
+            .iter()
+            .map(|(engine, count)| format!("{} {}", engine, count))
+            .collect();
+        write!(out, " ({})", engines.join(", "))?;
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Size:      {} of {}",
+        format_bytes(size),
+        format_bytes(CONFIG.cache.disk_max_bytes)
+    )?;
+    write!(out, "Hits:      {}, misses: {}", stats.hits, stats.misses)?;
+    if let Some(rate) = stats.hit_rate() {
+        write!(out, ", hit rate {:.1}%", rate * 100.0)?;
+    }
+    // Hits and misses are only counted per cache, not per entry
+    if !filter.is_empty() {
+        write!(out, " (whole cache, not filtered)")?;
+    }
+    Ok(out)
+}
+
+fn cache_list(cache: &DiskCache, filter: &CacheFilter) -> Result<String> {
+    let entries = cache.list(filter)?;
+    if entries.is_empty() {
+        return Ok("No cache entries".to_string());
+    }
+
+    let mut out = format!(
+        "{:<10} {:<6} {:>5} {:>7} {:>9} {:>6}  QUERY",
+        "ENGINE", "LOCALE", "START", "RESULTS", "SIZE", "AGE"
+    );
+    for entry in entries {
+        write!(
+            out,
+            "\n{:<10} {:<6} {:>5} {:>7} {:>9} {:>6}  {}",
+            entry.key.engine,
+            entry.key.locale,
+            entry.key.start,
+            entry.results,
+            format_bytes(entry.size),
+            format_age(entry.age()),
+            entry.key.query
+        )?;
+    }
+    Ok(out)
+}
+
+/// Format a size in bytes with a binary unit
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Format an age in seconds in its largest whole unit
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
+}
@@ -2,11 +2,15 @@ use anyhow::Result;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::{
-    fs,
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
     path::{Path, PathBuf},
 };
 
-use crate::error::types::ConfigError;
+use crate::{
+    core::keymap::{Keymap, Mode},
+    error::types::{ConfigError, SearchError},
+};
 
 /// Global configuration instance, lazily initialized when first accessed
 pub static CONFIG: Lazy<Config> =
@@ -15,13 +19,34 @@ pub static CONFIG: Lazy<Config> =
 /// Cache configuration settings
 #[derive(Debug, Deserialize)]
 pub struct CacheConfig {
-    /// Maximum number of items that can be stored in the cache
-    #[serde(default = "default_max_capacity")]
-    pub max_capacity: u64,
+    /// Maximum total size of the in-memory cache in bytes
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: u64,
 
     /// Time-to-live for cached items in seconds
     #[serde(default = "default_time_to_live")]
     pub time_to_live: u64,
+
+    /// Seconds past the time-to-live during which a cached page is still
+    /// shown while it is refreshed in the background; 0 disables this
+    #[serde(default = "default_stale_window")]
+    pub stale_window: u64,
+
+    /// Also keep the raw HTML of cached pages, for debugging parsers
+    #[serde(default)]
+    pub keep_html: bool,
+
+    /// Keep cached responses on disk between sessions
+    #[serde(default)]
+    pub disk: bool,
+
+    /// Directory of the on-disk cache
+    #[serde(default = "default_disk_dir")]
+    pub disk_dir: PathBuf,
+
+    /// Maximum total size of the on-disk cache in bytes
+    #[serde(default = "default_disk_max_bytes")]
+    pub disk_max_bytes: u64,
 }
 
 impl CacheConfig {
@@ -31,6 +56,11 @@ impl CacheConfig {
                 "Cache time-to-live must be greater than 0".to_string(),
             )));
         }
+        if self.disk && self.disk_max_bytes == 0 {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "Disk cache size must be greater than 0".to_string(),
+            )));
+        }
         Ok(())
     }
 }
@@ -41,6 +71,30 @@ pub struct EngineConfig {
     /// Preferred search engine (google, bing, duckduckgo)
     #[serde(default = "default_favor")]
     pub favor: String,
+
+    /// Base URLs of the search engines
+    #[serde(default)]
+    pub base_urls: BaseUrls,
+}
+
+/// Base URLs the search engines send requests to, overridable for testing
+#[derive(Debug, Deserialize)]
+pub struct BaseUrls {
+    /// Google base URL
+    #[serde(default = "default_google_url")]
+    pub google: String,
+
+    /// Bing base URL
+    #[serde(default = "default_bing_url")]
+    pub bing: String,
+
+    /// DuckDuckGo base URL
+    #[serde(default = "default_duckduckgo_url")]
+    pub duckduckgo: String,
+
+    /// DuckDuckGo autocomplete base URL, which the HTML-only host lacks
+    #[serde(default = "default_duckduckgo_suggest_url")]
+    pub duckduckgo_suggest: String,
 }
 
 impl EngineConfig {
@@ -65,11 +119,7 @@ pub struct SearchConfig {
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
 
-    /// Base delay between requests in milliseconds
-    #[serde(default = "default_base_delay")]
-    pub base_delay: u64,
-
-    /// Maximum random jitter added to delay in milliseconds
+    /// Maximum random jitter added to rate-limited requests in milliseconds
     #[serde(default = "default_max_jitter")]
     pub max_jitter: u64,
 
@@ -80,6 +130,14 @@ pub struct SearchConfig {
     /// Response timeout in seconds
     #[serde(default = "default_response_timeout")]
     pub response_timeout: u64,
+
+    /// Preferred result language, sent as `Accept-Language`
+    #[serde(default = "default_locale")]
+    pub locale: String,
+
+    /// Load the next results page in the background once the last one is shown
+    #[serde(default = "default_prefetch")]
+    pub prefetch: bool,
 }
 
 impl SearchConfig {
@@ -98,6 +156,401 @@ impl SearchConfig {
     }
 }
 
+/// Request rate allowed for an engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Sustained number of requests per minute
+    pub requests_per_minute: u32,
+    /// Number of requests that may be sent at once
+    pub burst: u32,
+}
+
+/// Per-engine override of the rate limit
+#[derive(Debug, Default, Deserialize)]
+pub struct EngineRateLimit {
+    /// Sustained number of requests per minute
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+
+    /// Number of requests that may be sent at once
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+/// Rate limit configuration settings, applied per engine with a token bucket
+#[derive(Debug, Deserialize)]
+pub struct RateLimitConfig {
+    /// Sustained number of requests per minute for each engine
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+
+    /// Number of requests each engine may receive at once
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+
+    /// Per-engine overrides, keyed by engine name
+    #[serde(default)]
+    pub engines: HashMap<String, EngineRateLimit>,
+}
+
+impl RateLimitConfig {
+    /// Get the rate limit that applies to an engine
+    pub fn for_engine(&self, engine: &str) -> RateLimit {
+        let engine_limit = self.engines.get(&engine.to_lowercase());
+        RateLimit {
+            requests_per_minute: engine_limit
+                .and_then(|limit| limit.requests_per_minute)
+                .unwrap_or(self.requests_per_minute),
+            burst: engine_limit
+                .and_then(|limit| limit.burst)
+                .unwrap_or(self.burst),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(name) = self
+            .engines
+            .keys()
+            .find(|name| !matches!(name.as_str(), "google" | "bing" | "duckduckgo"))
+        {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                "Unknown engine in rate limit settings: {}",
+                name
+            ))));
+        }
+        for engine in ["google", "bing", "duckduckgo"] {
+            let limit = self.for_engine(engine);
+            if limit.requests_per_minute == 0 || limit.burst == 0 {
+                return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                    "Rate limit for {} must allow at least one request",
+                    engine
+                ))));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Retry policy for failed requests
+#[derive(Debug, Deserialize)]
+pub struct RetryConfig {
+    /// Error kinds worth retrying (network, timeout, rate_limited, blocked, invalid_response, ...)
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<String>,
+
+    /// Initial backoff before the first retry in milliseconds, doubled on every retry
+    #[serde(default = "default_backoff_base")]
+    pub backoff_base: u64,
+
+    /// Upper bound for a single backoff in milliseconds
+    #[serde(default = "default_backoff_max")]
+    pub backoff_max: u64,
+
+    /// Total time allowed for all attempts of a request in seconds
+    #[serde(default = "default_deadline")]
+    pub deadline: u64,
+
+    /// Wait as long as the engine asks in a `Retry-After` header
+    #[serde(default = "default_respect_retry_after")]
+    pub respect_retry_after: bool,
+}
+
+impl RetryConfig {
+    fn validate(&self) -> Result<()> {
+        if let Some(kind) = self
+            .retry_on
+            .iter()
+            .find(|kind| !SearchError::KINDS.contains(&kind.as_str()))
+        {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                "Unknown error kind in retry_on: {}",
+                kind
+            ))));
+        }
+        if self.backoff_base > self.backoff_max {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "Backoff base must not exceed backoff max".to_string(),
+            )));
+        }
+        if self.deadline == 0 {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "Retry deadline must be greater than 0".to_string(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// HTTP recording settings, used for deterministic tests and bug reports
+#[derive(Debug, Deserialize)]
+pub struct RecordingConfig {
+    /// Transport mode: "off" (live network), "record" or "replay"
+    #[serde(default = "default_recording_mode")]
+    pub mode: String,
+
+    /// Directory where request/response pairs are stored
+    #[serde(default = "default_recording_dir")]
+    pub dir: PathBuf,
+}
+
+impl RecordingConfig {
+    fn validate(&self) -> Result<()> {
+        match self.mode.as_str() {
+            "off" | "record" | "replay" => Ok(()),
+            _ => Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "Recording mode must be one of off, record, replay".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Cookie jar settings
+#[derive(Debug, Deserialize)]
+pub struct CookieConfig {
+    /// Keep cookies on disk between sessions; disable for privacy
+    #[serde(default = "default_persist_cookies")]
+    pub persist: bool,
+
+    /// Directory holding one cookie file per engine
+    #[serde(default = "default_cookie_dir")]
+    pub dir: PathBuf,
+}
+
+/// Search history settings
+#[derive(Debug, Deserialize)]
+pub struct HistoryConfig {
+    /// Record submitted queries; disable to start every session incognito
+    #[serde(default = "default_record_history")]
+    pub record: bool,
+
+    /// Number of queries kept, dropping the oldest beyond it
+    #[serde(default = "default_history_max_entries")]
+    pub max_entries: usize,
+
+    /// Keep only the latest use of a query that is searched again
+    #[serde(default = "default_history_dedupe")]
+    pub dedupe: bool,
+
+    /// File holding the history, one JSON entry per line
+    #[serde(default = "default_history_path")]
+    pub path: PathBuf,
+}
+
+impl HistoryConfig {
+    fn validate(&self) -> Result<()> {
+        if self.max_entries == 0 {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "History size must be greater than 0".to_string(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Query autocomplete settings
+#[derive(Debug, Deserialize)]
+pub struct SuggestConfig {
+    /// Show suggestions under the input box while typing
+    #[serde(default = "default_suggest_enabled")]
+    pub enabled: bool,
+
+    /// Ask the engine's autocomplete endpoint, not only the history
+    #[serde(default = "default_suggest_remote")]
+    pub remote: bool,
+
+    /// Milliseconds without typing before the engine is asked
+    #[serde(default = "default_suggest_debounce")]
+    pub debounce: u64,
+
+    /// Maximum number of suggestions shown
+    #[serde(default = "default_suggest_max_items")]
+    pub max_items: usize,
+}
+
+impl SuggestConfig {
+    fn validate(&self) -> Result<()> {
+        if self.enabled && self.max_items == 0 {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(
+                "Number of suggestions must be greater than 0".to_string(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Key binding settings
+///
+/// Each mode table maps key chords like `"ctrl+r"` to action names, on top of
+/// the bindings of the preset; the action `"none"` removes a binding.
+#[derive(Debug, Deserialize)]
+pub struct KeysConfig {
+    /// Bindings to start from: "default", "vim" or "emacs"
+    #[serde(default = "default_keys_preset")]
+    pub preset: String,
+
+    /// Bindings that apply in every mode
+    #[serde(default)]
+    pub global: BTreeMap<String, String>,
+
+    /// Bindings while browsing the results
+    #[serde(default)]
+    pub results: BTreeMap<String, String>,
+
+    /// Bindings while editing the query
+    #[serde(default)]
+    pub input: BTreeMap<String, String>,
+
+    /// Bindings while the suggestion dropdown is shown
+    #[serde(default)]
+    pub suggestions: BTreeMap<String, String>,
+
+    /// Bindings in the Ctrl-R history search popup
+    #[serde(default)]
+    pub history_search: BTreeMap<String, String>,
+
+    /// Bindings in the help overlay
+    #[serde(default)]
+    pub help: BTreeMap<String, String>,
+}
+
+impl KeysConfig {
+    /// Bindings configured for a mode, by chord
+    pub fn bindings(&self, mode: Mode) -> &BTreeMap<String, String> {
+        match mode {
+            Mode::Global => &self.global,
+            Mode::Results => &self.results,
+            Mode::Input => &self.input,
+            Mode::Suggestions => &self.suggestions,
+            Mode::HistorySearch => &self.history_search,
+            Mode::Help => &self.help,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        Keymap::from_config(self)?;
+        Ok(())
+    }
+}
+
+/// Address of a local Tor daemon's SOCKS port, used by the `tor` preset
+pub const TOR_PROXY_URL: &str = "socks5h://127.0.0.1:9050";
+
+/// Proxy server settings for all engines or a single engine
+#[derive(Default, Clone, Deserialize)]
+pub struct ProxySettings {
+    /// Proxy URL (http://, https://, socks5:// or socks5h://), or "direct" to bypass proxies
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Username for proxy authentication
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Password for proxy authentication
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Route requests through a local Tor daemon
+    #[serde(default)]
+    pub tor: bool,
+}
+
+impl ProxySettings {
+    /// Whether these settings select a proxy (or "direct")
+    pub fn is_set(&self) -> bool {
+        self.url.is_some() || self.tor
+    }
+
+    /// Effective proxy URL, with the Tor preset applied
+    pub fn proxy_url(&self) -> Option<&str> {
+        if self.tor {
+            Some(TOR_PROXY_URL)
+        } else {
+            self.url.as_deref()
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        if self.tor && self.url.is_some() {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                "Proxy for {} cannot set both url and tor",
+                name
+            ))));
+        }
+        if let Some(url) = self.url.as_deref().filter(|url| *url != "direct") {
+            let scheme = url.split("://").next().unwrap_or_default();
+            if !url.contains("://") || !matches!(scheme, "http" | "https" | "socks5" | "socks5h") {
+                return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                    "Invalid proxy URL for {}: {}",
+                    name, url
+                ))));
+            }
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                "Proxy password for {} requires a username",
+                name
+            ))));
+        }
+        Ok(())
+    }
+}
+
+// Written by hand so the password never ends up in logs or error messages
+impl fmt::Debug for ProxySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxySettings")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("tor", &self.tor)
+            .finish()
+    }
+}
+
+/// Proxy configuration settings
+///
+/// Without a configured proxy the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY`
+/// and `NO_PROXY` environment variables are honored.
+#[derive(Debug, Default, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy used by every engine without its own settings
+    #[serde(flatten)]
+    pub global: ProxySettings,
+
+    /// Hosts that bypass the proxy, in `NO_PROXY` syntax
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    /// Per-engine proxy settings, keyed by engine name
+    #[serde(default)]
+    pub engines: HashMap<String, ProxySettings>,
+}
+
+impl ProxyConfig {
+    /// Get the proxy settings that apply to an engine
+    pub fn for_engine(&self, engine: &str) -> Option<&ProxySettings> {
+        self.engines
+            .get(&engine.to_lowercase())
+            .filter(|settings| settings.is_set())
+            .or(Some(&self.global).filter(|settings| settings.is_set()))
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.global.validate("all engines")?;
+        for (name, settings) in &self.engines {
+            if !matches!(name.as_str(), "google" | "bing" | "duckduckgo") {
+                return Err(anyhow::anyhow!(ConfigError::ValidationError(format!(
+                    "Unknown engine in proxy settings: {}",
+                    name
+                ))));
+            }
+            settings.validate(name)?;
+        }
+        Ok(())
+    }
+}
+
 /// Main configuration structure containing all settings
 #[derive(Debug, Deserialize, Default)]
 pub struct Config {
@@ -112,6 +565,38 @@ pub struct Config {
     /// Search engine preferences
     #[serde(default)]
     pub engine: EngineConfig,
+
+    /// Rate limit settings
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    /// Retry policy settings
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// HTTP recording settings
+    #[serde(default)]
+    pub recording: RecordingConfig,
+
+    /// Proxy settings
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+
+    /// Cookie jar settings
+    #[serde(default)]
+    pub cookies: CookieConfig,
+
+    /// Search history settings
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Query autocomplete settings
+    #[serde(default)]
+    pub suggest: SuggestConfig,
+
+    /// Key binding settings
+    #[serde(default)]
+    pub keys: KeysConfig,
 }
 
 impl Config {
@@ -121,6 +606,8 @@ impl Config {
     /// - Windows: %APPDATA%\st\config.toml or %USERPROFILE%\AppData\Roaming\st\config.toml
     /// - Other: ~/.config/st/config.toml
     ///
+    /// The `ST_CONFIG` environment variable overrides this location.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
@@ -131,11 +618,19 @@ impl Config {
     pub fn new() -> Result<Self> {
         let config_path = Self::config_path()?;
         Self::ensure_config_dir(&config_path)?;
+        Self::load(&config_path)
+    }
 
-        let config = if let Ok(content) = fs::read_to_string(&config_path) {
-            toml::from_str(&content).unwrap_or(Config::default())
-        } else {
-            Config::default()
+    /// Reads and validates the configuration file at `path`
+    ///
+    /// A missing file gives the defaults. A file that can't be parsed is an
+    /// error rather than ignored, so settings like a proxy are never dropped
+    /// without telling the user.
+    pub fn load(path: &Path) -> Result<Self> {
+        let config = match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(ConfigError::ParseError)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(e.into()),
         };
 
         config.validate()?;
@@ -144,6 +639,10 @@ impl Config {
 
     /// Returns the path to the configuration file
     fn config_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os("ST_CONFIG") {
+            return Ok(PathBuf::from(path));
+        }
+
         let config_dir = if cfg!(target_os = "windows") {
             dirs::config_dir().unwrap_or_else(|| {
                 dirs::home_dir()
@@ -160,6 +659,18 @@ impl Config {
         Ok(config_dir.join("st").join("config.toml"))
     }
 
+    /// Returns the directory for state kept between sessions, like cookies
+    ///
+    /// - Linux: $XDG_STATE_HOME/st or ~/.local/state/st
+    /// - Windows: %LOCALAPPDATA%\st
+    /// - macOS: ~/Library/Application Support/st
+    pub fn state_dir() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("st"))
+            .unwrap_or_else(|| PathBuf::from(".st"))
+    }
+
     /// Ensures the configuration directory exists
     fn ensure_config_dir(config_path: &Path) -> Result<()> {
         if let Some(parent) = config_path.parent() {
@@ -175,6 +686,13 @@ impl Config {
         self.search.validate()?;
         self.cache.validate()?;
         self.engine.validate()?;
+        self.rate_limit.validate()?;
+        self.retry.validate()?;
+        self.recording.validate()?;
+        self.proxy.validate()?;
+        self.history.validate()?;
+        self.suggest.validate()?;
+        self.keys.validate()?;
         Ok(())
     }
 }
@@ -194,9 +712,6 @@ fn default_user_agents() -> Vec<String> {
 fn default_max_retries() -> u32 {
     3
 }
-fn default_base_delay() -> u64 {
-    1000
-}
 fn default_max_jitter() -> u64 {
     1000
 }
@@ -206,15 +721,108 @@ fn default_request_timeout() -> u64 {
 fn default_response_timeout() -> u64 {
     10
 }
-fn default_max_capacity() -> u64 {
-    100
+fn default_locale() -> String {
+    "en-US".to_string()
+}
+fn default_prefetch() -> bool {
+    true
+}
+fn default_max_bytes() -> u64 {
+    10 * 1024 * 1024
 }
 fn default_time_to_live() -> u64 {
     600
 }
+fn default_stale_window() -> u64 {
+    600
+}
+fn default_disk_dir() -> PathBuf {
+    Config::state_dir().join("cache")
+}
+fn default_disk_max_bytes() -> u64 {
+    50 * 1024 * 1024
+}
 fn default_favor() -> String {
     "google".to_string()
 }
+fn default_google_url() -> String {
+    "https://www.google.com".to_string()
+}
+fn default_bing_url() -> String {
+    "https://www.bing.com".to_string()
+}
+fn default_duckduckgo_url() -> String {
+    "https://html.duckduckgo.com".to_string()
+}
+fn default_duckduckgo_suggest_url() -> String {
+    "https://duckduckgo.com".to_string()
+}
+fn default_requests_per_minute() -> u32 {
+    20
+}
+fn default_burst() -> u32 {
+    4
+}
+fn default_retry_on() -> Vec<String> {
+    ["network", "timeout", "rate_limited", "invalid_response"]
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect()
+}
+fn default_backoff_base() -> u64 {
+    500
+}
+fn default_backoff_max() -> u64 {
+    10_000
+}
+fn default_deadline() -> u64 {
+    30
+}
+fn default_respect_retry_after() -> bool {
+    true
+}
+fn default_recording_mode() -> String {
+    "off".to_string()
+}
+fn default_persist_cookies() -> bool {
+    true
+}
+fn default_cookie_dir() -> PathBuf {
+    Config::state_dir().join("cookies")
+}
+fn default_record_history() -> bool {
+    true
+}
+fn default_history_max_entries() -> usize {
+    1000
+}
+fn default_history_dedupe() -> bool {
+    true
+}
+fn default_history_path() -> PathBuf {
+    Config::state_dir().join("history.jsonl")
+}
+fn default_suggest_enabled() -> bool {
+    true
+}
+fn default_suggest_remote() -> bool {
+    true
+}
+fn default_suggest_debounce() -> u64 {
+    250
+}
+fn default_suggest_max_items() -> usize {
+    8
+}
+fn default_keys_preset() -> String {
+    "default".to_string()
+}
+fn default_recording_dir() -> PathBuf {
+    Config::config_path()
+        .ok()
+        .and_then(|path| path.parent().map(|dir| dir.join("recordings")))
+        .unwrap_or_else(|| PathBuf::from("recordings"))
+}
 
 /// Default implementation for SearchConfig
 impl Default for SearchConfig {
@@ -222,10 +830,11 @@ impl Default for SearchConfig {
         Self {
             user_agents: default_user_agents(),
             max_retries: default_max_retries(),
-            base_delay: default_base_delay(),
             max_jitter: default_max_jitter(),
             request_timeout: default_request_timeout(),
             response_timeout: default_response_timeout(),
+            locale: default_locale(),
+            prefetch: default_prefetch(),
         }
     }
 }
@@ -234,8 +843,13 @@ impl Default for SearchConfig {
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
-            max_capacity: default_max_capacity(),
+            max_bytes: default_max_bytes(),
+            keep_html: false,
             time_to_live: default_time_to_live(),
+            stale_window: default_stale_window(),
+            disk: false,
+            disk_dir: default_disk_dir(),
+            disk_max_bytes: default_disk_max_bytes(),
         }
     }
 }
@@ -245,6 +859,102 @@ impl Default for EngineConfig {
     fn default() -> Self {
         Self {
             favor: default_favor(),
+            base_urls: BaseUrls::default(),
+        }
+    }
+}
+
+/// Default implementation for BaseUrls
+impl Default for BaseUrls {
+    fn default() -> Self {
+        Self {
+            google: default_google_url(),
+            bing: default_bing_url(),
+            duckduckgo: default_duckduckgo_url(),
+            duckduckgo_suggest: default_duckduckgo_suggest_url(),
+        }
+    }
+}
+
+/// Default implementation for RateLimitConfig
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: default_requests_per_minute(),
+            burst: default_burst(),
+            engines: HashMap::new(),
+        }
+    }
+}
+
+/// Default implementation for RetryConfig
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            retry_on: default_retry_on(),
+            backoff_base: default_backoff_base(),
+            backoff_max: default_backoff_max(),
+            deadline: default_deadline(),
+            respect_retry_after: default_respect_retry_after(),
+        }
+    }
+}
+
+/// Default implementation for RecordingConfig
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_recording_mode(),
+            dir: default_recording_dir(),
+        }
+    }
+}
+
+/// Default implementation for CookieConfig
+impl Default for CookieConfig {
+    fn default() -> Self {
+        Self {
+            persist: default_persist_cookies(),
+            dir: default_cookie_dir(),
+        }
+    }
+}
+
+/// Default implementation for HistoryConfig
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            record: default_record_history(),
+            max_entries: default_history_max_entries(),
+            dedupe: default_history_dedupe(),
+            path: default_history_path(),
+        }
+    }
+}
+
+/// Default implementation for SuggestConfig
+impl Default for SuggestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_suggest_enabled(),
+            remote: default_suggest_remote(),
+            debounce: default_suggest_debounce(),
+            max_items: default_suggest_max_items(),
+        }
+    }
+}
+
+/// Default implementation for KeysConfig
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            preset: default_keys_preset(),
+            global: BTreeMap::new(),
+            results: BTreeMap::new(),
+            input: BTreeMap::new(),
+            suggestions: BTreeMap::new(),
+            history_search: BTreeMap::new(),
+            help: BTreeMap::new(),
         }
     }
 }
@@ -0,0 +1,208 @@
+//! Single-line text editing for the query input
+//!
+//! The cursor is a byte offset into the text that always sits on a grapheme
+//! cluster boundary, so an emoji or a letter with combining accents moves and
+//! deletes as one character. Widths are measured in terminal columns.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::core::keymap::{Action, Keymap, Mode};
+
+/// Cursor movement or deletion in the text being edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edit {
+    MoveLeft,
+    MoveRight,
+    MoveWordLeft,
+    MoveWordRight,
+    MoveToStart,
+    MoveToEnd,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteToStart,
+    DeleteToEnd,
+}
+
+impl Edit {
+    /// Stable name of the edit, as used for key bindings
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edit::MoveLeft => "move_left",
+            Edit::MoveRight => "move_right",
+            Edit::MoveWordLeft => "move_word_left",
+            Edit::MoveWordRight => "move_word_right",
+            Edit::MoveToStart => "move_to_start",
+            Edit::MoveToEnd => "move_to_end",
+            Edit::DeleteBackward => "delete_backward",
+            Edit::DeleteForward => "delete_forward",
+            Edit::DeleteWordBackward => "delete_word_backward",
+            Edit::DeleteToStart => "delete_to_start",
+            Edit::DeleteToEnd => "delete_to_end",
+        }
+    }
+
+    /// What the edit does, as shown in the help overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            Edit::MoveLeft => "Move left",
+            Edit::MoveRight => "Move right",
+            Edit::MoveWordLeft => "Move to the previous word",
+            Edit::MoveWordRight => "Move to the next word",
+            Edit::MoveToStart => "Move to the start",
+            Edit::MoveToEnd => "Move to the end",
+            Edit::DeleteBackward => "Delete the character before the cursor",
+            Edit::DeleteForward => "Delete the character under the cursor",
+            Edit::DeleteWordBackward => "Delete the word before the cursor",
+            Edit::DeleteToStart => "Delete to the start",
+            Edit::DeleteToEnd => "Delete to the end",
+        }
+    }
+}
+
+/// Apply an editing key to `text`, returning whether the key was handled
+///
+/// Keys bound to an [`Edit`] in the input mode of `keymap` edit the text;
+/// other characters typed without Ctrl or Alt, or with AltGr, are inserted
+/// at the cursor.
+pub fn handle_key(text: &mut String, cursor: &mut usize, key: KeyEvent, keymap: &Keymap) -> bool {
+    match keymap.action(Mode::Input, key) {
+        Some(Action::Edit(edit)) => apply(text, cursor, edit),
+        Some(_) => return false,
+        None => match key.code {
+            KeyCode::Char(c) if is_text(key.modifiers) => insert(text, cursor, c),
+            _ => return false,
+        },
+    }
+    true
+}
+
+/// Whether a character typed with `modifiers` is text to insert
+///
+/// AltGr, which layouts use for characters like `@` or `€`, is reported as
+/// Ctrl and Alt together, so only one of them marks a shortcut.
+fn is_text(modifiers: KeyModifiers) -> bool {
+    let shortcut = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+    shortcut.is_empty() || shortcut == KeyModifiers::CONTROL | KeyModifiers::ALT
+}
+
+/// Apply `edit` to `text` at the cursor
+pub fn apply(text: &mut String, cursor: &mut usize, edit: Edit) {
+    *cursor = clamp(text, *cursor);
+    match edit {
+        Edit::MoveLeft => *cursor = prev_boundary(text, *cursor),
+        Edit::MoveRight => *cursor = next_boundary(text, *cursor),
+        Edit::MoveWordLeft => *cursor = prev_word(text, *cursor),
+        Edit::MoveWordRight => *cursor = next_word(text, *cursor),
+        Edit::MoveToStart => *cursor = 0,
+        Edit::MoveToEnd => *cursor = text.len(),
+        Edit::DeleteBackward => delete_to(text, cursor, prev_boundary(text, *cursor)),
+        Edit::DeleteForward => delete_to(text, cursor, next_boundary(text, *cursor)),
+        Edit::DeleteWordBackward => delete_to(text, cursor, prev_word(text, *cursor)),
+        Edit::DeleteToStart => delete_to(text, cursor, 0),
+        Edit::DeleteToEnd => delete_to(text, cursor, text.len()),
+    }
+}
+
+/// Insert `c` at the cursor and move the cursor past it
+pub fn insert(text: &mut String, cursor: &mut usize, c: char) {
+    *cursor = clamp(text, *cursor);
+    text.insert(*cursor, c);
+    *cursor += c.len_utf8();
+    // A combining mark joins the grapheme before the cursor
+    *cursor = clamp(text, *cursor);
+}
+
+/// Part of `text` to show in `width` columns so the cursor stays visible
+///
+/// Returns the visible slice and the cursor's column within it. Text scrolls
+/// horizontally only as far as needed to keep the cursor in view.
+pub fn visible_window(text: &str, cursor: usize, width: usize) -> (&str, usize) {
+    let cursor = clamp(text, cursor);
+    // The cursor takes a column of its own when it is at the end
+    let width = width.max(1);
+    let mut start = 0;
+    while text[start..cursor].width() >= width {
+        start = next_boundary(text, start);
+    }
+
+    let mut end = start;
+    let mut used = 0;
+    for grapheme in text[start..].graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width > width {
+            break;
+        }
+        used += grapheme_width;
+        end += grapheme.len();
+    }
+    (&text[start..end], text[start..cursor].width())
+}
+
+/// Move `cursor` to the nearest grapheme boundary at or before it
+pub fn clamp(text: &str, cursor: usize) -> usize {
+    if cursor >= text.len() {
+        return text.len();
+    }
+    text.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .take_while(|&index| index <= cursor)
+        .last()
+        .unwrap_or(0)
+}
+
+fn prev_boundary(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(cursor, |grapheme| cursor + grapheme.len())
+}
+
+/// Start of the word before the cursor, skipping separators first
+fn prev_word(text: &str, cursor: usize) -> usize {
+    let mut graphemes = text[..cursor].grapheme_indices(true).rev().peekable();
+    while graphemes.next_if(|(_, g)| !is_word(g)).is_some() {}
+    let mut start = graphemes.peek().map_or(0, |&(index, _)| index);
+    while let Some((index, _)) = graphemes.next_if(|(_, g)| is_word(g)) {
+        start = index;
+    }
+    start
+}
+
+/// End of the word after the cursor, skipping separators first
+fn next_word(text: &str, cursor: usize) -> usize {
+    let mut end = cursor;
+    let mut graphemes = text[cursor..].graphemes(true).peekable();
+    while let Some(g) = graphemes.next_if(|g| !is_word(g)) {
+        end += g.len();
+    }
+    while let Some(g) = graphemes.next_if(|g| is_word(g)) {
+        end += g.len();
+    }
+    end
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Delete the text between the cursor and `to`, leaving the cursor at the
+/// start of the deleted range
+fn delete_to(text: &mut String, cursor: &mut usize, to: usize) {
+    let (from, to) = if to < *cursor {
+        (to, *cursor)
+    } else {
+        (*cursor, to)
+    };
+    text.replace_range(from..to, "");
+    *cursor = from;
+}
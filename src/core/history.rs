@@ -0,0 +1,224 @@
+//! Search history kept between sessions
+//!
+//! Every submitted query is appended to a file in the state directory, one
+//! JSON entry per line, oldest first. The file is read again before each
+//! change so that several sessions running at once don't lose each other's
+//! queries, and it is only readable by the current user.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{
+    core::config::HistoryConfig,
+    search::{cache::unix_now, transport::write_private},
+};
+
+/// A submitted query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Query as it was typed
+    pub query: String,
+    /// Engine the query was sent to, or "All" for every engine
+    pub engine: String,
+    /// When the query was submitted, in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl HistoryEntry {
+    /// Seconds since the query was submitted
+    pub fn age(&self) -> u64 {
+        unix_now().saturating_sub(self.timestamp)
+    }
+}
+
+/// File a history is kept in, with the rules for adding to it
+///
+/// Cheap to clone, so saving can move to the blocking thread pool.
+#[derive(Debug, Clone)]
+pub struct HistoryFile {
+    path: PathBuf,
+    max_entries: usize,
+    dedupe: bool,
+}
+
+impl HistoryFile {
+    /// Add an entry to the file, keeping the ones other sessions saved
+    /// meanwhile, and return all saved entries
+    ///
+    /// Blocks on file I/O.
+    pub fn append(&self, entry: HistoryEntry) -> io::Result<Vec<HistoryEntry>> {
+        // Saves of this process must not read and write the file interleaved
+        static SAVING: Mutex<()> = Mutex::new(());
+        let _saving = SAVING.lock().unwrap();
+
+        let mut entries = read_entries(&self.path);
+        self.insert(&mut entries, entry);
+        self.save(&entries)?;
+        Ok(entries)
+    }
+
+    /// Add an entry to `entries`, dropping an earlier use of the same query
+    /// with de-duplication and the oldest entries beyond the maximum
+    fn insert(&self, entries: &mut Vec<HistoryEntry>, entry: HistoryEntry) {
+        if self.dedupe {
+            entries.retain(|existing| existing.query != entry.query);
+        }
+        entries.push(entry);
+        let excess = entries.len().saturating_sub(self.max_entries);
+        entries.drain(..excess);
+    }
+
+    /// Write all entries, replacing the file in one step
+    fn save(&self, entries: &[HistoryEntry]) -> io::Result<()> {
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_private(&self.path, content.as_bytes())
+    }
+}
+
+/// Submitted queries, oldest first
+#[derive(Debug)]
+pub struct History {
+    file: HistoryFile,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Load the history kept at `path`, or start an empty one
+    ///
+    /// Lines that can't be parsed are skipped rather than failing the load.
+    pub fn load(path: PathBuf, max_entries: usize, dedupe: bool) -> Self {
+        let entries = read_entries(&path);
+        Self {
+            file: HistoryFile {
+                path,
+                max_entries,
+                dedupe,
+            },
+            entries,
+        }
+    }
+
+    /// Load the history configured in `[history]`
+    pub fn from_config(config: &HistoryConfig) -> Self {
+        Self::load(config.path.clone(), config.max_entries, config.dedupe)
+    }
+
+    /// File the history is kept in
+    pub fn path(&self) -> &Path {
+        &self.file.path
+    }
+
+    /// File the history is kept in, to save entries to
+    pub fn file(&self) -> &HistoryFile {
+        &self.file
+    }
+
+    /// All entries, oldest first
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Entry `back` steps from the newest one, which is 0
+    pub fn get(&self, back: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().rev().nth(back)
+    }
+
+    /// Record a submitted query and save the history
+    ///
+    /// Blank queries are ignored. With de-duplication, an earlier use of the
+    /// same query is dropped so it only appears as the newest entry.
+    pub fn add(&mut self, query: &str, engine: &str) -> io::Result<()> {
+        if let Some(entry) = self.push(query, engine) {
+            self.entries = self.file.append(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Record a submitted query in memory only, returning the entry to save
+    /// with [`HistoryFile::append`]
+    ///
+    /// Blank queries are ignored and give `None`.
+    pub fn push(&mut self, query: &str, engine: &str) -> Option<HistoryEntry> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        let entry = HistoryEntry {
+            query: query.to_string(),
+            engine: engine.to_string(),
+            timestamp: unix_now(),
+        };
+        self.file.insert(&mut self.entries, entry.clone());
+        Some(entry)
+    }
+
+    /// Entries matching `pattern`, best match first
+    ///
+    /// Matching is fuzzy: the characters of the pattern must appear in the
+    /// query in order, but not necessarily next to each other. Equally good
+    /// matches are ordered newest first, and each query is listed once.
+    pub fn search(&self, pattern: &str) -> Vec<&HistoryEntry> {
+        let mut seen = HashSet::new();
+        let mut matches: Vec<_> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| seen.insert(entry.query.as_str()))
+            .filter_map(|entry| fuzzy_score(pattern, &entry.query).map(|score| (score, entry)))
+            .collect();
+        matches.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// How well `pattern` matches `text`, or `None` if it doesn't
+///
+/// Case and whitespace in the pattern are ignored. Characters matched right
+/// after the previous one or at the start of a word score higher.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous = None;
+
+    for c in pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let found = position + text[position..].iter().position(|&t| t == c)?;
+        score += 1;
+        if found > 0 && previous == Some(found - 1) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+fn read_entries(path: &Path) -> Vec<HistoryEntry> {
+    fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
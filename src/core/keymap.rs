@@ -0,0 +1,755 @@
+//! Key bindings: which key chord triggers which action in each mode
+//!
+//! Key handling looks actions up here instead of matching on keys, so the
+//! help overlay and the hints in the status bar are generated from the same
+//! bindings that are in effect.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    core::{config::KeysConfig, editor::Edit},
+    error::types::ConfigError,
+};
+
+/// Part of the interface a key press is applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Checked before every other mode
+    Global,
+    /// Browsing the results
+    Results,
+    /// Editing the query
+    Input,
+    /// Suggestion dropdown under the input box, while it is shown
+    Suggestions,
+    /// Ctrl-R history search popup
+    HistorySearch,
+    /// Help overlay
+    Help,
+}
+
+impl Mode {
+    /// All modes, in the order the help lists them
+    pub const ALL: [Mode; 6] = [
+        Mode::Global,
+        Mode::Results,
+        Mode::Input,
+        Mode::Suggestions,
+        Mode::HistorySearch,
+        Mode::Help,
+    ];
+
+    /// Stable name of the mode
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Global => "global",
+            Mode::Results => "results",
+            Mode::Input => "input",
+            Mode::Suggestions => "suggestions",
+            Mode::HistorySearch => "history_search",
+            Mode::Help => "help",
+        }
+    }
+
+    /// Heading of the mode in the help overlay
+    pub fn title(&self) -> &'static str {
+        match self {
+            Mode::Global => "Everywhere",
+            Mode::Results => "Results",
+            Mode::Input => "Editing the query",
+            Mode::Suggestions => "Suggestions",
+            Mode::HistorySearch => "History search (Ctrl+R)",
+            Mode::Help => "Help",
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = ConfigError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Mode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| ConfigError::ValidationError(format!("Unknown key mode: {}", name)))
+    }
+}
+
+/// Something a key press can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Cancel the running search and close the history search
+    Cancel,
+    /// Show the help overlay
+    ShowHelp,
+    /// Quit the application
+    Quit,
+    /// Start editing the query
+    EditInput,
+    /// Clear the results and the query
+    ClearResults,
+    /// Toggle answering only from the cache
+    ToggleOffline,
+    /// Toggle keeping queries out of the history
+    ToggleIncognito,
+    /// Search again, bypassing the cache
+    Refresh,
+    /// Switch to the next engine
+    NextEngine,
+    /// Toggle searching all engines at once
+    ToggleAllEngines,
+    /// Open the selected result in the browser
+    OpenUrl,
+    /// Select the next item of a list
+    SelectNext,
+    /// Select the previous item of a list
+    SelectPrevious,
+    /// Show the previous results page
+    PreviousPage,
+    /// Show the next results page
+    NextPage,
+    /// Cancel the running search
+    CancelSearch,
+    /// Search for the query
+    Submit,
+    /// Stop editing the query
+    StopEditing,
+    /// Recall an older query from the history
+    HistoryOlder,
+    /// Recall a newer query from the history
+    HistoryNewer,
+    /// Open the history search popup
+    SearchHistory,
+    /// Search for the selected item
+    Accept,
+    /// Copy the selected item into the query for editing
+    AcceptForEditing,
+    /// Close the popup or list
+    Close,
+    /// Scroll down a line
+    ScrollDown,
+    /// Scroll up a line
+    ScrollUp,
+    /// Scroll down a page
+    PageDown,
+    /// Scroll up a page
+    PageUp,
+    /// Scroll to the top
+    ScrollToTop,
+    /// Scroll to the bottom
+    ScrollToBottom,
+    /// Edit the text being typed
+    Edit(Edit),
+}
+
+impl Action {
+    /// All actions
+    pub const ALL: [Action; 41] = [
+        Action::Cancel,
+        Action::ShowHelp,
+        Action::Quit,
+        Action::EditInput,
+        Action::ClearResults,
+        Action::ToggleOffline,
+        Action::ToggleIncognito,
+        Action::Refresh,
+        Action::NextEngine,
+        Action::ToggleAllEngines,
+        Action::OpenUrl,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::PreviousPage,
+        Action::NextPage,
+        Action::CancelSearch,
+        Action::Submit,
+        Action::StopEditing,
+        Action::HistoryOlder,
+        Action::HistoryNewer,
+        Action::SearchHistory,
+        Action::Accept,
+        Action::AcceptForEditing,
+        Action::Close,
+        Action::ScrollDown,
+        Action::ScrollUp,
+        Action::PageDown,
+        Action::PageUp,
+        Action::ScrollToTop,
+        Action::ScrollToBottom,
+        Action::Edit(Edit::MoveLeft),
+        Action::Edit(Edit::MoveRight),
+        Action::Edit(Edit::MoveWordLeft),
+        Action::Edit(Edit::MoveWordRight),
+        Action::Edit(Edit::MoveToStart),
+        Action::Edit(Edit::MoveToEnd),
+        Action::Edit(Edit::DeleteBackward),
+        Action::Edit(Edit::DeleteForward),
+        Action::Edit(Edit::DeleteWordBackward),
+        Action::Edit(Edit::DeleteToStart),
+        Action::Edit(Edit::DeleteToEnd),
+    ];
+
+    /// Stable name of the action
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Cancel => "cancel",
+            Action::ShowHelp => "show_help",
+            Action::Quit => "quit",
+            Action::EditInput => "edit_input",
+            Action::ClearResults => "clear_results",
+            Action::ToggleOffline => "toggle_offline",
+            Action::ToggleIncognito => "toggle_incognito",
+            Action::Refresh => "refresh",
+            Action::NextEngine => "next_engine",
+            Action::ToggleAllEngines => "toggle_all_engines",
+            Action::OpenUrl => "open_url",
+            Action::SelectNext => "select_next",
+            Action::SelectPrevious => "select_previous",
+            Action::PreviousPage => "previous_page",
+            Action::NextPage => "next_page",
+            Action::CancelSearch => "cancel_search",
+            Action::Submit => "submit",
+            Action::StopEditing => "stop_editing",
+            Action::HistoryOlder => "history_older",
+            Action::HistoryNewer => "history_newer",
+            Action::SearchHistory => "search_history",
+            Action::Accept => "accept",
+            Action::AcceptForEditing => "accept_for_editing",
+            Action::Close => "close",
+            Action::ScrollDown => "scroll_down",
+            Action::ScrollUp => "scroll_up",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::ScrollToTop => "scroll_to_top",
+            Action::ScrollToBottom => "scroll_to_bottom",
+            Action::Edit(edit) => edit.name(),
+        }
+    }
+
+    /// What the action does, as shown in the help overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Cancel => "Cancel the running search",
+            Action::ShowHelp => "Show this help",
+            Action::Quit => "Quit",
+            Action::EditInput => "Edit the query",
+            Action::ClearResults => "Clear results",
+            Action::ToggleOffline => "Toggle offline mode",
+            Action::ToggleIncognito => "Toggle incognito mode (no history)",
+            Action::Refresh => "Refresh results, bypassing the cache",
+            Action::NextEngine => "Switch to the next engine",
+            Action::ToggleAllEngines => "Toggle searching all engines at once",
+            Action::OpenUrl => "Open selected result",
+            Action::SelectNext => "Select the next item",
+            Action::SelectPrevious => "Select the previous item",
+            Action::PreviousPage => "Previous page",
+            Action::NextPage => "Next page",
+            Action::CancelSearch => "Cancel the running search",
+            Action::Submit => "Search",
+            Action::StopEditing => "Stop editing",
+            Action::HistoryOlder => "Recall an older query",
+            Action::HistoryNewer => "Recall a newer query",
+            Action::SearchHistory => "Search the history",
+            Action::Accept => "Search for the selected item",
+            Action::AcceptForEditing => "Copy the selected item into the query",
+            Action::Close => "Close",
+            Action::ScrollDown => "Scroll down",
+            Action::ScrollUp => "Scroll up",
+            Action::PageDown => "Scroll down a page",
+            Action::PageUp => "Scroll up a page",
+            Action::ScrollToTop => "Scroll to the top",
+            Action::ScrollToBottom => "Scroll to the bottom",
+            Action::Edit(edit) => edit.description(),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ConfigError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| ConfigError::ValidationError(format!("Unknown key action: {}", name)))
+    }
+}
+
+/// A key with its modifiers, like Ctrl+R
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// The chord of a key press
+    ///
+    /// Shift is dropped from characters, which carry it as their case, and
+    /// from Shift+Tab, which terminals report as its own key.
+    pub fn from_event(key: KeyEvent) -> Self {
+        let mut modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if key.modifiers.contains(KeyModifiers::SHIFT)
+            && !matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab)
+        {
+            modifiers |= KeyModifiers::SHIFT;
+        }
+        Self {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = ConfigError;
+
+    /// Parse a chord like `ctrl+r`, `alt+b`, `shift+tab`, `pagedown` or `G`
+    ///
+    /// Modifier and key names are case-insensitive; a single character is
+    /// taken as is, so `g` and `G` are different chords. Terminals report
+    /// Shift as part of the character, so `shift+g` is read as `G`, and Shift
+    /// with any other character is rejected.
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let invalid = || ConfigError::ValidationError(format!("Invalid key: {:?}", chord));
+        // A lone "+" is the plus key rather than a separator
+        let (modifier_names, key) = match chord.rsplit_once('+') {
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').ok_or_else(invalid)?, "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", chord),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(upper), None)
+                        if upper != c && !modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        modifiers -= KeyModifiers::SHIFT;
+                        KeyCode::Char(upper)
+                    }
+                    _ => {
+                        return Err(ConfigError::ValidationError(format!(
+                            "Invalid key: {:?}, write the shifted character instead",
+                            chord
+                        )))
+                    }
+                }
+            }
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                    modifiers -= KeyModifiers::SHIFT;
+                    KeyCode::BackTab
+                }
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(invalid()),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+/// Default bindings, as mode, chord and action names
+const DEFAULT_BINDINGS: &[(&str, &str, &str)] = &[
+    ("global", "ctrl+c", "cancel"),
+    ("global", "f1", "show_help"),
+    ("results", "?", "show_help"),
+    ("results", "j", "select_next"),
+    ("results", "down", "select_next"),
+    ("results", "k", "select_previous"),
+    ("results", "up", "select_previous"),
+    ("results", "h", "previous_page"),
+    ("results", "left", "previous_page"),
+    ("results", "l", "next_page"),
+    ("results", "right", "next_page"),
+    ("results", "r", "clear_results"),
+    ("results", "enter", "open_url"),
+    ("results", "i", "edit_input"),
+    ("results", "q", "quit"),
+    ("results", "e", "next_engine"),
+    ("results", "a", "toggle_all_engines"),
+    ("results", "o", "toggle_offline"),
+    ("results", "R", "refresh"),
+    ("results", "p", "toggle_incognito"),
+    ("results", "esc", "cancel_search"),
+    ("input", "enter", "submit"),
+    ("input", "esc", "stop_editing"),
+    ("input", "up", "history_older"),
+    ("input", "ctrl+p", "history_older"),
+    ("input", "down", "history_newer"),
+    ("input", "ctrl+n", "history_newer"),
+    ("input", "ctrl+r", "search_history"),
+    ("input", "left", "move_left"),
+    ("input", "ctrl+b", "move_left"),
+    ("input", "right", "move_right"),
+    ("input", "ctrl+f", "move_right"),
+    ("input", "alt+b", "move_word_left"),
+    ("input", "ctrl+left", "move_word_left"),
+    ("input", "alt+left", "move_word_left"),
+    ("input", "alt+f", "move_word_right"),
+    ("input", "ctrl+right", "move_word_right"),
+    ("input", "alt+right", "move_word_right"),
+    ("input", "home", "move_to_start"),
+    ("input", "ctrl+a", "move_to_start"),
+    ("input", "end", "move_to_end"),
+    ("input", "ctrl+e", "move_to_end"),
+    ("input", "backspace", "delete_backward"),
+    ("input", "ctrl+h", "delete_backward"),
+    ("input", "delete", "delete_forward"),
+    ("input", "ctrl+d", "delete_forward"),
+    ("input", "ctrl+w", "delete_word_backward"),
+    ("input", "alt+backspace", "delete_word_backward"),
+    ("input", "ctrl+backspace", "delete_word_backward"),
+    ("input", "ctrl+u", "delete_to_start"),
+    ("input", "ctrl+k", "delete_to_end"),
+    ("suggestions", "tab", "select_next"),
+    ("suggestions", "down", "select_next"),
+    ("suggestions", "shift+tab", "select_previous"),
+    ("suggestions", "up", "select_previous"),
+    ("suggestions", "enter", "accept"),
+    ("suggestions", "esc", "close"),
+    ("history_search", "enter", "accept"),
+    ("history_search", "tab", "accept_for_editing"),
+    ("history_search", "down", "select_next"),
+    ("history_search", "ctrl+n", "select_next"),
+    ("history_search", "ctrl+r", "select_next"),
+    ("history_search", "up", "select_previous"),
+    ("history_search", "ctrl+p", "select_previous"),
+    ("history_search", "esc", "close"),
+    ("history_search", "ctrl+g", "close"),
+    ("help", "j", "scroll_down"),
+    ("help", "down", "scroll_down"),
+    ("help", "k", "scroll_up"),
+    ("help", "up", "scroll_up"),
+    ("help", "pagedown", "page_down"),
+    ("help", "space", "page_down"),
+    ("help", "pageup", "page_up"),
+    ("help", "g", "scroll_to_top"),
+    ("help", "home", "scroll_to_top"),
+    ("help", "G", "scroll_to_bottom"),
+    ("help", "end", "scroll_to_bottom"),
+    ("help", "esc", "close"),
+    ("help", "q", "close"),
+    ("help", "?", "close"),
+    ("help", "f1", "close"),
+];
+
+/// Bindings the `vim` preset adds to the defaults
+const VIM_BINDINGS: &[(&str, &str, &str)] = &[
+    ("results", "/", "edit_input"),
+    ("results", "ctrl+f", "next_page"),
+    ("results", "ctrl+d", "next_page"),
+    ("results", "ctrl+b", "previous_page"),
+    ("results", "ctrl+u", "previous_page"),
+    ("suggestions", "ctrl+n", "select_next"),
+    ("suggestions", "ctrl+p", "select_previous"),
+    ("history_search", "ctrl+j", "select_next"),
+    ("history_search", "ctrl+k", "select_previous"),
+    ("help", "ctrl+f", "page_down"),
+    ("help", "ctrl+d", "page_down"),
+    ("help", "ctrl+b", "page_up"),
+    ("help", "ctrl+u", "page_up"),
+];
+
+/// Bindings the `emacs` preset adds to the defaults
+const EMACS_BINDINGS: &[(&str, &str, &str)] = &[
+    ("results", "ctrl+n", "select_next"),
+    ("results", "ctrl+p", "select_previous"),
+    ("results", "ctrl+v", "next_page"),
+    ("results", "alt+v", "previous_page"),
+    ("results", "ctrl+s", "edit_input"),
+    ("results", "ctrl+g", "cancel_search"),
+    ("input", "ctrl+g", "stop_editing"),
+    ("suggestions", "ctrl+n", "select_next"),
+    ("suggestions", "ctrl+p", "select_previous"),
+    ("suggestions", "ctrl+g", "close"),
+    ("help", "ctrl+n", "scroll_down"),
+    ("help", "ctrl+p", "scroll_up"),
+    ("help", "ctrl+v", "page_down"),
+    ("help", "alt+v", "page_up"),
+    ("help", "alt+<", "scroll_to_top"),
+    ("help", "alt+>", "scroll_to_bottom"),
+    ("help", "ctrl+g", "close"),
+];
+
+/// Names of the presets a `[keys]` table can start from
+pub const PRESETS: [&str; 3] = ["default", "vim", "emacs"];
+
+/// Actions summarized in the status bar of a mode, with their short labels
+const HINTS: &[(Mode, &[Action], &str)] = &[
+    (Mode::Results, &[Action::ShowHelp], "Help"),
+    (
+        Mode::Results,
+        &[Action::SelectNext, Action::SelectPrevious],
+        "Navigate",
+    ),
+    (
+        Mode::Results,
+        &[Action::PreviousPage, Action::NextPage],
+        "Page",
+    ),
+    (Mode::Results, &[Action::ClearResults], "Clear"),
+    (Mode::Results, &[Action::OpenUrl], "Open"),
+    (Mode::Results, &[Action::EditInput], "Input"),
+    (Mode::Results, &[Action::Quit], "Quit"),
+    (Mode::Results, &[Action::NextEngine], "Engine"),
+    (Mode::Results, &[Action::ToggleAllEngines], "All"),
+    (Mode::Results, &[Action::ToggleOffline], "Offline"),
+    (Mode::Results, &[Action::Refresh], "Refresh"),
+    (Mode::Results, &[Action::ToggleIncognito], "Incognito"),
+    (Mode::Input, &[Action::ShowHelp], "Help"),
+    (
+        Mode::Input,
+        &[Action::Edit(Edit::DeleteToStart)],
+        "Clear Input",
+    ),
+    (
+        Mode::Input,
+        &[Action::HistoryOlder, Action::HistoryNewer],
+        "History",
+    ),
+    (Mode::Input, &[Action::SearchHistory], "Search history"),
+    (Mode::Input, &[Action::StopEditing], "Exit input mode"),
+    (Mode::Input, &[Action::Submit], "Search"),
+    (
+        Mode::Help,
+        &[Action::ScrollDown, Action::ScrollUp],
+        "Scroll",
+    ),
+    (Mode::Help, &[Action::Close], "Close"),
+    (Mode::Suggestions, &[Action::SelectNext], "Select"),
+    (Mode::Suggestions, &[Action::Accept], "Search"),
+    (Mode::Suggestions, &[Action::Close], "Hide"),
+    (Mode::HistorySearch, &[Action::Accept], "Search"),
+    (Mode::HistorySearch, &[Action::AcceptForEditing], "Edit"),
+    (Mode::HistorySearch, &[Action::Close], "Close"),
+];
+
+/// Bindings of key chords to actions, per mode
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    /// Bindings in the order they were added, which the help follows
+    bindings: Vec<(Mode, KeyChord, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: Vec::new(),
+        };
+        keymap
+            .bind_all(DEFAULT_BINDINGS.iter().copied())
+            .expect("Invalid default key binding");
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Keymap of a preset: `default`, or `vim` or `emacs`, which add their
+    /// bindings to the defaults
+    pub fn preset(name: &str) -> Result<Self, ConfigError> {
+        let mut keymap = Self::default();
+        let extra = match name {
+            "default" => &[][..],
+            "vim" => VIM_BINDINGS,
+            "emacs" => EMACS_BINDINGS,
+            _ => {
+                return Err(ConfigError::ValidationError(format!(
+                    "Unknown key preset: {} (expected one of {})",
+                    name,
+                    PRESETS.join(", ")
+                )))
+            }
+        };
+        keymap.bind_all(extra.iter().copied())?;
+        Ok(keymap)
+    }
+
+    /// Keymap of the `[keys]` config section: its preset with the bindings
+    /// of the per-mode tables on top
+    ///
+    /// Binding a chord to `none` removes its binding from the preset.
+    pub fn from_config(config: &KeysConfig) -> Result<Self, ConfigError> {
+        let mut keymap = Self::preset(&config.preset)?;
+        for mode in Mode::ALL {
+            for (chord, action) in config.bindings(mode) {
+                keymap.bind_all([(mode.name(), chord.as_str(), action.as_str())])?;
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Apply bindings given as mode, chord and action names, in order
+    fn bind_all<'a>(
+        &mut self,
+        bindings: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    ) -> Result<(), ConfigError> {
+        for (mode, chord, action) in bindings {
+            let mode: Mode = mode.parse()?;
+            let chord: KeyChord = chord.parse().map_err(|_| {
+                ConfigError::ValidationError(format!(
+                    "Invalid key in [keys.{}]: {:?}",
+                    mode.name(),
+                    chord
+                ))
+            })?;
+            if action == "none" {
+                self.unbind(mode, chord);
+                continue;
+            }
+            let action = action.parse().map_err(|_| {
+                ConfigError::ValidationError(format!(
+                    "Unknown action for {} in [keys.{}]: {}",
+                    chord,
+                    mode.name(),
+                    action
+                ))
+            })?;
+            self.bind(mode, chord, action);
+        }
+        Ok(())
+    }
+
+    /// Bind a chord to an action, replacing its previous binding in `mode`
+    pub fn bind(&mut self, mode: Mode, chord: KeyChord, action: Action) {
+        self.unbind(mode, chord);
+        self.bindings.push((mode, chord, action));
+    }
+
+    /// Remove the binding of a chord in `mode`, if any
+    pub fn unbind(&mut self, mode: Mode, chord: KeyChord) {
+        self.bindings
+            .retain(|&(bound_mode, bound, _)| bound_mode != mode || bound != chord);
+    }
+
+    /// Action bound to a key press in `mode`
+    pub fn action(&self, mode: Mode, key: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from_event(key);
+        self.bindings
+            .iter()
+            .find(|&&(bound_mode, bound, _)| bound_mode == mode && bound == chord)
+            .map(|&(_, _, action)| action)
+    }
+
+    /// Chords bound to an action in `mode`, in binding order
+    pub fn chords(&self, mode: Mode, action: Action) -> Vec<KeyChord> {
+        self.bindings
+            .iter()
+            .filter(|&&(bound_mode, _, bound)| bound_mode == mode && bound == action)
+            .map(|&(_, chord, _)| chord)
+            .collect()
+    }
+
+    /// First chord bound to an action in `mode`, or globally
+    pub fn key(&self, mode: Mode, action: Action) -> Option<KeyChord> {
+        self.chords(mode, action)
+            .first()
+            .or(self.chords(Mode::Global, action).first())
+            .copied()
+    }
+
+    /// Bindings of every mode for the help overlay: for each mode with
+    /// bindings, its actions with all their chords, like `"j, Down"`
+    pub fn help(&self) -> Vec<(Mode, Vec<(String, &'static str)>)> {
+        Mode::ALL
+            .into_iter()
+            .filter_map(|mode| {
+                let mut actions: Vec<Action> = Vec::new();
+                for &(bound_mode, _, action) in &self.bindings {
+                    if bound_mode == mode && !actions.contains(&action) {
+                        actions.push(action);
+                    }
+                }
+                let entries: Vec<_> = actions
+                    .into_iter()
+                    .map(|action| {
+                        let chords: Vec<String> = self
+                            .chords(mode, action)
+                            .iter()
+                            .map(KeyChord::to_string)
+                            .collect();
+                        (chords.join(", "), action.description())
+                    })
+                    .collect();
+                (!entries.is_empty()).then_some((mode, entries))
+            })
+            .collect()
+    }
+
+    /// Short summary of the main keys of `mode` for the status bar, like
+    /// `"j/k: Navigate | h/l: Page"`
+    ///
+    /// Global bindings count for every mode; unbound actions are left out.
+    pub fn hint(&self, mode: Mode) -> String {
+        HINTS
+            .iter()
+            .filter(|(hint_mode, _, _)| *hint_mode == mode)
+            .filter_map(|(_, actions, label)| {
+                let keys: Option<Vec<String>> = actions
+                    .iter()
+                    .map(|&action| self.key(mode, action).map(|chord| chord.to_string()))
+                    .collect();
+                keys.map(|keys| format!("{}: {}", keys.join("/"), label))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
@@ -1,2 +1,6 @@
 pub mod app;
+pub mod cli;
 pub mod config;
+pub mod editor;
+pub mod history;
+pub mod keymap;
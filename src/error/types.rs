@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 /// Search error types
@@ -7,10 +8,16 @@ pub enum SearchError {
     NetworkError(String),
 
     #[error("Rate limited by search engine")]
-    RateLimited,
+    RateLimited {
+        /// Delay requested by the engine through the `Retry-After` header
+        retry_after: Option<Duration>,
+    },
 
-    #[error("Search engine blocked the request")]
-    Blocked,
+    #[error("Search engine blocked the request: {reason}")]
+    Blocked {
+        /// What gave the block away, e.g. a CAPTCHA redirect
+        reason: String,
+    },
 
     #[error("Invalid response from search engine: {0}")]
     InvalidResponse(String),
@@ -24,10 +31,53 @@ pub enum SearchError {
     #[error("No search results found")]
     NoResults,
 
+    #[error("No cached results for this search (offline mode)")]
+    NotCached,
+
+    #[error("No recording for {url} in {dir}")]
+    RecordingMissing {
+        /// URL of the request that was never recorded
+        url: String,
+        /// Directory searched for the recording
+        dir: String,
+    },
+
     #[error("Unknown error occurred")]
     Unknown,
 }
 
+impl SearchError {
+    /// Names of all error kinds returned by [`SearchError::kind`]
+    pub const KINDS: [&'static str; 10] = [
+        "network",
+        "rate_limited",
+        "blocked",
+        "invalid_response",
+        "timeout",
+        "other",
+        "no_results",
+        "not_cached",
+        "recording_missing",
+        "unknown",
+    ];
+
+    /// Stable name of the error kind, as used in the `[retry]` configuration
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SearchError::NetworkError(_) => "network",
+            SearchError::RateLimited { .. } => "rate_limited",
+            SearchError::Blocked { .. } => "blocked",
+            SearchError::InvalidResponse(_) => "invalid_response",
+            SearchError::Timeout => "timeout",
+            SearchError::Other(_) => "other",
+            SearchError::NoResults => "no_results",
+            SearchError::NotCached => "not_cached",
+            SearchError::RecordingMissing { .. } => "recording_missing",
+            SearchError::Unknown => "unknown",
+        }
+    }
+}
+
 /// Configuration error types
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -1,6 +1,8 @@
 pub mod core;
 pub mod error;
 pub mod search;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod ui;
 
 pub use core::app::App;
@@ -17,6 +19,19 @@ use ratatui::prelude::*;
 use std::io::stdout;
 
 pub async fn run() -> Result<()> {
+    let (offline, incognito, query) = match core::cli::CliCommand::parse(std::env::args().skip(1))?
+    {
+        core::cli::CliCommand::Tui {
+            offline,
+            incognito,
+            query,
+        } => (offline, incognito, query),
+        command => {
+            println!("{}", command.execute()?);
+            return Ok(());
+        }
+    };
+
     enable_raw_mode()?;
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -24,11 +39,18 @@ pub async fn run() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::new();
+    app.offline = offline;
+    app.incognito |= incognito;
+    if let Some(query) = query {
+        app.cursor = query.len();
+        app.input = query;
+    }
     let res = ui::terminal::run_app(&mut terminal, &mut app).await;
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
+    search::engine::RESPONSE_CACHE.save_stats().await;
 
     if let Err(err) = res {
         println!("Error: {}", err);
@@ -0,0 +1,532 @@
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::transport::{fnv1a, write_private};
+use crate::{core::config::CacheConfig, SearchResult};
+
+/// Identifies a results page independently of how its URL is built
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// Engine name, lowercased
+    pub engine: String,
+    /// Query, lowercased with whitespace collapsed
+    pub query: String,
+    /// Index of the first result on the page
+    pub start: u16,
+    /// Locale the results were requested in
+    pub locale: String,
+}
+
+impl CacheKey {
+    /// Create a normalized key, so equivalent queries share an entry
+    pub fn new(engine: &str, query: &str, start: u16, locale: &str) -> Self {
+        Self {
+            engine: engine.to_lowercase(),
+            query: query
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase(),
+            start,
+            locale: locale.to_lowercase(),
+        }
+    }
+}
+
+/// Parsed results page as stored in the cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedPage {
+    /// Parsed results, empty if the page had none
+    pub results: Vec<SearchResult>,
+    /// Raw HTML, only kept with `[cache] keep_html`
+    #[serde(default)]
+    pub html: Option<String>,
+    /// When the page was fetched, in seconds since the Unix epoch
+    #[serde(default)]
+    pub fetched_at: u64,
+}
+
+impl CachedPage {
+    /// Create a page fetched just now
+    pub fn new(results: Vec<SearchResult>, html: Option<String>) -> Self {
+        Self {
+            results,
+            html,
+            fetched_at: unix_now(),
+        }
+    }
+
+    /// Seconds since the page was fetched
+    pub fn age(&self) -> u64 {
+        unix_now().saturating_sub(self.fetched_at)
+    }
+
+    /// Approximate memory used by the page in bytes
+    pub fn size(&self) -> usize {
+        let results: usize = self
+            .results
+            .iter()
+            .map(|result| {
+                std::mem::size_of::<SearchResult>()
+                    + result.title.len()
+                    + result.url.len()
+                    + result.description.len()
+            })
+            .sum();
+        std::mem::size_of::<Self>() + results + self.html.as_ref().map_or(0, String::len)
+    }
+}
+
+/// How usable a cached page is, from its age
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Within the first four fifths of its time-to-live
+    Fresh,
+    /// Within the last fifth of its time-to-live; worth refreshing ahead
+    Expiring,
+    /// Past its time-to-live but within the stale window; shown while refreshing
+    Stale,
+    /// Past the stale window; only shown in offline mode
+    Expired,
+}
+
+impl Freshness {
+    /// Classify a page of the given age in seconds
+    pub fn of(age: u64, time_to_live: u64, stale_window: u64) -> Self {
+        if age.saturating_mul(5) < time_to_live.saturating_mul(4) {
+            Freshness::Fresh
+        } else if age < time_to_live {
+            Freshness::Expiring
+        } else if age < time_to_live.saturating_add(stale_window) {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+}
+
+/// Cache lookups that were answered or missed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Searches answered from the cache
+    pub hits: u64,
+    /// Searches that had to ask the engine, or failed offline
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, if there were any
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+/// Selects cache entries to list or purge; empty filters select everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheFilter {
+    /// Only entries of this engine, compared case-insensitively
+    pub engine: Option<String>,
+    /// Only entries whose normalized query contains this text
+    pub query: Option<String>,
+    /// Only entries fetched at least this long ago
+    pub older_than: Option<Duration>,
+}
+
+impl CacheFilter {
+    /// Whether no filter is set, so every entry is selected
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether an entry with this key and page is selected
+    pub fn matches(&self, key: &CacheKey, page: &CachedPage) -> bool {
+        self.engine
+            .as_ref()
+            .is_none_or(|engine| key.engine == engine.to_lowercase())
+            && self.query.as_ref().is_none_or(|query| {
+                let query = CacheKey::new("", query, 0, "").query;
+                key.query.contains(&query)
+            })
+            && self
+                .older_than
+                .is_none_or(|older_than| page.age() >= older_than.as_secs())
+    }
+}
+
+/// Results cache kept in memory and, if enabled, on disk
+///
+/// The memory tier lives as long as the process and is bounded by the byte
+/// size of its entries; the disk tier lets a new session answer repeated
+/// searches without touching the network. Memory entries are kept through the
+/// stale window so they can be shown while being refreshed.
+#[derive(Debug)]
+pub struct ResponseCache {
+    memory: Cache<CacheKey, CachedPage>,
+    disk: Option<DiskCache>,
+    keep_html: bool,
+    time_to_live: u64,
+    stale_window: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    /// Counts of this session already added to the disk totals
+    saved: Mutex<CacheStats>,
+}
+
+impl ResponseCache {
+    /// Create the cache from the `[cache]` settings
+    pub fn from_config(config: &CacheConfig) -> Self {
+        Self {
+            memory: Cache::builder()
+                .weigher(|key: &CacheKey, page: &CachedPage| {
+                    let size = std::mem::size_of::<CacheKey>()
+                        + key.engine.len()
+                        + key.query.len()
+                        + key.locale.len()
+                        + page.size();
+                    size.try_into().unwrap_or(u32::MAX)
+                })
+                .max_capacity(config.max_bytes)
+                .time_to_live(Duration::from_secs(
+                    config.time_to_live.saturating_add(config.stale_window),
+                ))
+                .build(),
+            disk: DiskCache::from_config(config),
+            keep_html: config.keep_html,
+            time_to_live: config.time_to_live,
+            stale_window: config.stale_window,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            saved: Mutex::default(),
+        }
+    }
+
+    /// Whether pages should be stored with their raw HTML
+    pub fn keeps_html(&self) -> bool {
+        self.keep_html
+    }
+
+    /// How usable a page from this cache is
+    pub fn freshness(&self, page: &CachedPage) -> Freshness {
+        Freshness::of(page.age(), self.time_to_live, self.stale_window)
+    }
+
+    /// Get a page younger than the time-to-live
+    pub async fn get(&self, key: &CacheKey) -> Option<CachedPage> {
+        self.lookup(key)
+            .await
+            .filter(|page| page.age() < self.time_to_live)
+    }
+
+    /// Get a page regardless of its age, promoting disk entries into memory
+    ///
+    /// Pages past the stale window only survive in the disk tier.
+    pub async fn lookup(&self, key: &CacheKey) -> Option<CachedPage> {
+        if let Some(page) = self.memory.get(key).await {
+            return Some(page);
+        }
+        let page = self.disk.as_ref()?.get_any(key)?;
+        self.memory.insert(key.clone(), page.clone()).await;
+        Some(page)
+    }
+
+    /// Count a search answered from the cache (`hit`) or not
+    ///
+    /// Counts stay in memory until [`ResponseCache::save_stats`].
+    pub fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add the counts recorded since the last save to the totals of the
+    /// disk tier, which `st cache stats` reports across sessions
+    ///
+    /// Called when the session ends; the write runs on the blocking thread pool.
+    pub async fn save_stats(&self) {
+        let Some(disk) = self.disk.clone() else {
+            return;
+        };
+        let stats = self.stats();
+        let unsaved = {
+            let mut saved = self.saved.lock().unwrap();
+            let unsaved = CacheStats {
+                hits: stats.hits - saved.hits,
+                misses: stats.misses - saved.misses,
+            };
+            *saved = stats;
+            unsaved
+        };
+        if unsaved != CacheStats::default() {
+            // Statistics are informational; losing a count is harmless
+            let _ = tokio::task::spawn_blocking(move || disk.add_stats(unsaved)).await;
+        }
+    }
+
+    /// Hits and misses of this session
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Store a page in every tier
+    ///
+    /// The disk write runs on the blocking thread pool.
+    pub async fn insert(&self, key: CacheKey, page: CachedPage) {
+        if let Some(disk) = self.disk.clone() {
+            let (key, page) = (key.clone(), page.clone());
+            // A failed write only means a cache miss next session
+            let _ = tokio::task::spawn_blocking(move || disk.insert(&key, &page)).await;
+        }
+        self.memory.insert(key, page).await;
+    }
+}
+
+/// Cached page as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    key: CacheKey,
+    page: CachedPage,
+}
+
+/// Summary of a disk cache entry, for listing and purging
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// Key of the cached page
+    pub key: CacheKey,
+    /// When the page was fetched, in seconds since the Unix epoch
+    pub fetched_at: u64,
+    /// Number of cached results
+    pub results: usize,
+    /// Size of the entry file in bytes
+    pub size: u64,
+}
+
+impl CacheEntry {
+    /// Seconds since the page was fetched
+    pub fn age(&self) -> u64 {
+        unix_now().saturating_sub(self.fetched_at)
+    }
+}
+
+/// Size-bounded response cache with one JSON file per entry
+///
+/// Entries reveal what the user searched for, so like the cookie jars they
+/// are only readable by the current user.
+///
+/// The total size is measured on the first insert and then tracked as
+/// entries are written, so the directory is only scanned again when the
+/// cache grows past its limit.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    time_to_live: Duration,
+    tracked_size: Arc<Mutex<Option<u64>>>,
+}
+
+impl DiskCache {
+    /// Create a disk cache in `dir`, evicting the oldest entries beyond `max_bytes`
+    pub fn new(dir: PathBuf, max_bytes: u64, time_to_live: Duration) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            time_to_live,
+            tracked_size: Arc::default(),
+        }
+    }
+
+    /// Create the disk cache if `[cache] disk` is enabled
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        config.disk.then(|| {
+            Self::new(
+                config.disk_dir.clone(),
+                config.disk_max_bytes,
+                Duration::from_secs(config.time_to_live),
+            )
+        })
+    }
+
+    /// Path of the file holding an entry
+    pub fn path(&self, key: &CacheKey) -> PathBuf {
+        let key_json = serde_json::to_string(key).unwrap_or_default();
+        self.dir.join(format!(
+            "{}-{:016x}.json",
+            key.engine,
+            fnv1a(key_json.as_bytes())
+        ))
+    }
+
+    /// Get an entry younger than the time-to-live
+    pub fn get(&self, key: &CacheKey) -> Option<CachedPage> {
+        self.get_any(key)
+            .filter(|page| page.age() < self.time_to_live.as_secs())
+    }
+
+    /// Get an entry regardless of its age
+    pub fn get_any(&self, key: &CacheKey) -> Option<CachedPage> {
+        let content = fs::read_to_string(self.path(key)).ok()?;
+        let entry: DiskEntry = serde_json::from_str(&content).ok()?;
+        (entry.key == *key).then_some(entry.page)
+    }
+
+    /// Store an entry, then evict the oldest ones if the cache outgrew its size
+    pub fn insert(&self, key: &CacheKey, page: &CachedPage) -> io::Result<()> {
+        let entry = DiskEntry {
+            key: key.clone(),
+            page: page.clone(),
+        };
+        let json = serde_json::to_string(&entry)?;
+        let path = self.path(key);
+        fs::create_dir_all(&self.dir)?;
+
+        let mut tracked_size = self.tracked_size.lock().unwrap();
+        let replaced = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        write_private(&path, json.as_bytes())?;
+        let size = match *tracked_size {
+            Some(size) => size.saturating_sub(replaced) + json.len() as u64,
+            None => self.size()?,
+        };
+        *tracked_size = Some(if size > self.max_bytes {
+            self.evict()?
+        } else {
+            size
+        });
+        Ok(())
+    }
+
+    /// Total size of all entries in bytes
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Entries selected by `filter`, newest first
+    pub fn list(&self, filter: &CacheFilter) -> io::Result<Vec<CacheEntry>> {
+        let mut entries: Vec<_> = self
+            .read_entries(filter)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.fetched_at));
+        Ok(entries)
+    }
+
+    /// Remove the entries selected by `filter`, returning how many were removed
+    pub fn purge(&self, filter: &CacheFilter) -> io::Result<usize> {
+        *self.tracked_size.lock().unwrap() = None;
+        let entries = self.read_entries(filter)?;
+        for (path, _) in &entries {
+            fs::remove_file(path)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Hits and misses counted across sessions
+    pub fn stats(&self) -> CacheStats {
+        fs::read_to_string(self.stats_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Add hits and misses to the totals kept next to the entries
+    ///
+    /// The totals are replaced through a rename, so other sessions never
+    /// read a partly written file.
+    pub fn add_stats(&self, stats: CacheStats) -> io::Result<()> {
+        let totals = self.stats();
+        let totals = CacheStats {
+            hits: totals.hits + stats.hits,
+            misses: totals.misses + stats.misses,
+        };
+        fs::create_dir_all(&self.dir)?;
+        write_private(
+            &self.stats_path(),
+            serde_json::to_string(&totals)?.as_bytes(),
+        )
+    }
+
+    /// Totals are kept without the `.json` extension so they aren't an entry
+    fn stats_path(&self) -> PathBuf {
+        self.dir.join("stats")
+    }
+
+    /// Path and summary of the readable entries selected by `filter`
+    fn read_entries(&self, filter: &CacheFilter) -> io::Result<Vec<(PathBuf, CacheEntry)>> {
+        let mut selected = Vec::new();
+        for (path, size, _) in self.entries()? {
+            let Some(entry) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<DiskEntry>(&content).ok())
+            else {
+                continue;
+            };
+            if filter.matches(&entry.key, &entry.page) {
+                selected.push((
+                    path,
+                    CacheEntry {
+                        fetched_at: entry.page.fetched_at,
+                        results: entry.page.results.len(),
+                        key: entry.key,
+                        size,
+                    },
+                ));
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Remove the oldest entries until the cache fits, returning its new size
+    fn evict(&self) -> io::Result<u64> {
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        // Oldest first
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, entry_size, _) in entries {
+            if size <= self.max_bytes {
+                break;
+            }
+            fs::remove_file(path)?;
+            size -= entry_size;
+        }
+        Ok(size)
+    }
+
+    /// Path, size and modification time of every entry
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = Vec::new();
+        for entry in dir {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let metadata = entry.metadata()?;
+                entries.push((path, metadata.len(), metadata.modified()?));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
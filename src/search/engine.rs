@@ -1,15 +1,24 @@
-use rand::{seq::SliceRandom, thread_rng, Rng};
-use std::time::Duration;
-use tokio::time::sleep;
 use anyhow::Result;
-use tokio::time::timeout;
-use once_cell::sync::Lazy;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use futures_util::{stream::FuturesUnordered, Stream};
 use moka::future::Cache;
+use once_cell::sync::Lazy;
+use rand::{seq::SliceRandom, thread_rng};
+use reqwest::Url;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tokio::time::timeout;
 
+use super::{
+    cache::{CacheKey, CachedPage, Freshness, ResponseCache},
+    rate_limit::RATE_LIMITER,
+    retry::RetryPolicy,
+    transport::{HttpResponse, Transport},
+};
 use crate::{SearchError, SearchResult, CONFIG};
 
 /// Search Engine Enum
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchEngine {
     Google(Google),
     Bing(Bing),
@@ -26,6 +35,15 @@ impl SearchEngine {
         }
     }
 
+    /// All search engines, in the order `next` cycles through them
+    pub fn all() -> Vec<Self> {
+        vec![
+            SearchEngine::Google(Google),
+            SearchEngine::Bing(Bing),
+            SearchEngine::DuckDuckGo(DuckDuckGo),
+        ]
+    }
+
     /// Get the next search engine in the sequence
     pub fn next(&self) -> Self {
         match self {
@@ -44,6 +62,36 @@ impl SearchEngine {
         }
     }
 
+    /// Parse a results page using the current search engine
+    pub fn parse(&self, html: &str) -> Result<Vec<SearchResult>> {
+        let document = scraper::Html::parse_document(html);
+        match self {
+            SearchEngine::Google(google) => google.parse(&document),
+            SearchEngine::Bing(bing) => bing.parse(&document),
+            SearchEngine::DuckDuckGo(duck_duck_go) => duck_duck_go.parse(&document),
+        }
+    }
+
+    /// Detect a CAPTCHA or block page using the current search engine
+    pub fn detect_block(&self, url: &str, html: &str) -> Option<String> {
+        let document = scraper::Html::parse_document(html);
+        match self {
+            SearchEngine::Google(google) => google.detect_block(url, &document),
+            SearchEngine::Bing(bing) => bing.detect_block(url, &document),
+            SearchEngine::DuckDuckGo(duck_duck_go) => duck_duck_go.detect_block(url, &document),
+        }
+    }
+
+    /// Detect a cookie consent interstitial using the current search engine
+    pub fn detect_consent(&self, url: &str, html: &str) -> bool {
+        let document = scraper::Html::parse_document(html);
+        match self {
+            SearchEngine::Google(google) => google.detect_consent(url, &document),
+            SearchEngine::Bing(bing) => bing.detect_consent(url, &document),
+            SearchEngine::DuckDuckGo(duck_duck_go) => duck_duck_go.detect_consent(url, &document),
+        }
+    }
+
     /// Perform a search using the current search engine
     pub async fn search(&self, query: &str, start: u16) -> Result<Vec<SearchResult>> {
         match self {
@@ -52,20 +100,253 @@ impl SearchEngine {
             SearchEngine::DuckDuckGo(duck_duck_go) => duck_duck_go.search(query, start).await,
         }
     }
+
+    /// Get autocomplete suggestions using the current search engine
+    pub async fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        match self {
+            SearchEngine::Google(google) => google.suggest(query).await,
+            SearchEngine::Bing(bing) => bing.suggest(query).await,
+            SearchEngine::DuckDuckGo(duck_duck_go) => duck_duck_go.suggest(query).await,
+        }
+    }
+
+    /// Get a results page using the current search engine
+    pub async fn search_page(
+        &self,
+        query: &str,
+        start: u16,
+        mode: CacheMode,
+    ) -> Result<SearchPage> {
+        match self {
+            SearchEngine::Google(google) => google.search_page(query, start, mode).await,
+            SearchEngine::Bing(bing) => bing.search_page(query, start, mode).await,
+            SearchEngine::DuckDuckGo(duck_duck_go) => {
+                duck_duck_go.search_page(query, start, mode).await
+            }
+        }
+    }
+}
+
+/// Search several engines at once, yielding each engine's page as it arrives
+///
+/// Batches come in the order the engines answer, so the fastest engine's
+/// results can be shown before the slowest one has responded. A failing
+/// engine yields a batch with its error and doesn't end the stream.
+pub fn search_stream(
+    engines: Vec<SearchEngine>,
+    query: &str,
+    start: u16,
+    mode: CacheMode,
+) -> impl Stream<Item = ResultBatch> + Send + 'static {
+    engines
+        .into_iter()
+        .map(|engine| {
+            let query = query.to_string();
+            async move {
+                ResultBatch {
+                    engine,
+                    start,
+                    page: engine.search_page(&query, start, mode).await,
+                }
+            }
+        })
+        .collect::<FuturesUnordered<_>>()
+}
+
+/// Results of one engine page, as produced by [`search_stream`]
+#[derive(Debug)]
+pub struct ResultBatch {
+    /// Engine the page comes from
+    pub engine: SearchEngine,
+    /// Index of the first result on the page
+    pub start: u16,
+    /// The page, or why it couldn't be fetched
+    pub page: Result<SearchPage>,
+}
+
+/// How a search may use the results cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Use cached results unless they are past the stale window, otherwise
+    /// ask the engine
+    #[default]
+    Normal,
+    /// Only use cached results, including expired ones; never touch the network
+    Offline,
+    /// Always ask the engine, replacing any cached results
+    Refresh,
+}
+
+/// Where the results of a page came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSource {
+    /// Fetched from the engine
+    Network,
+    /// Served from the cache within its time-to-live
+    Cache,
+    /// Served from the cache after its time-to-live, while refreshing or in
+    /// offline mode
+    Stale,
+}
+
+/// Results page returned by a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPage {
+    /// Parsed results
+    pub results: Vec<SearchResult>,
+    /// Where the results came from
+    pub source: PageSource,
+    /// Whether the cached results are due for a refresh, which the caller
+    /// should run in the background with [`CacheMode::Refresh`]
+    pub revalidate: bool,
 }
 
 /// Trait for search engines
 pub trait Engine {
     /// Get the name of the search engine
     fn name(&self) -> &'static str;
+    /// Get the base URL of the search engine
+    fn base_url(&self) -> &'static str;
     /// Build the URL for a search query
     fn build_url(&self, query: &str, start: u16) -> String;
+    /// Parse a search results page into search results
+    ///
+    /// The page is parsed into a document once per response and shared with
+    /// the detectors below.
+    fn parse(&self, document: &scraper::Html) -> Result<Vec<SearchResult>>;
+    /// Check whether a page fetched from `url` is a CAPTCHA or block page,
+    /// returning the reason if it is
+    fn detect_block(&self, url: &str, document: &scraper::Html) -> Option<String>;
+    /// Check whether a page fetched from `url` is a cookie consent interstitial
+    fn detect_consent(&self, _url: &str, _document: &scraper::Html) -> bool {
+        false
+    }
+    /// Cookies accepting the consent interstitial, in `Set-Cookie` syntax
+    fn consent_cookies(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// Build the URL of the autocomplete endpoint for a partial query, if
+    /// the engine has one
+    fn suggest_url(&self, _query: &str) -> Option<String> {
+        None
+    }
+    /// Get autocomplete suggestions for a partial query
+    ///
+    /// Engines without an autocomplete endpoint have no suggestions.
+    /// Suggestions are cached, but fetched only once without retries or rate
+    /// limiting: they are a convenience, and outdated as soon as the user
+    /// types on.
+    fn suggest(&self, query: &str) -> impl std::future::Future<Output = Result<Vec<String>>> {
+        async move {
+            let Some(url) = self.suggest_url(query) else {
+                return Ok(Vec::new());
+            };
+            let key = CacheKey::new(self.name(), query, 0, &CONFIG.search.locale);
+            if let Some(suggestions) = SUGGESTION_CACHE.get(&key).await {
+                return Ok(suggestions);
+            }
+
+            let user_agent = CONFIG
+                .search
+                .user_agents
+                .choose(&mut thread_rng())
+                .unwrap_or(&CONFIG.search.user_agents[0]);
+            let response = timeout(
+                Duration::from_secs(CONFIG.search.request_timeout),
+                TRANSPORT.get(self.name(), &url, user_agent),
+            )
+            .await
+            .map_err(|_| SearchError::Timeout)??;
+            if response.status != 200 {
+                return Err(anyhow::anyhow!(SearchError::InvalidResponse(format!(
+                    "Autocomplete answered with HTTP {}",
+                    response.status
+                ))));
+            }
+            let suggestions = parse_suggestions(&response.body)?;
+            SUGGESTION_CACHE.insert(key, suggestions.clone()).await;
+            Ok(suggestions)
+        }
+    }
     /// Perform a search
-    fn search(&self, query: &str, start: u16) -> impl std::future::Future<Output = Result<Vec<SearchResult>>>;
+    fn search(
+        &self,
+        query: &str,
+        start: u16,
+    ) -> impl std::future::Future<Output = Result<Vec<SearchResult>>> {
+        async move {
+            self.search_page(query, start, CacheMode::Normal)
+                .await
+                .map(|page| page.results)
+        }
+    }
+    /// Get a results page, from the cache or the network as `mode` allows
+    fn search_page(
+        &self,
+        query: &str,
+        start: u16,
+        mode: CacheMode,
+    ) -> impl std::future::Future<Output = Result<SearchPage>> {
+        async move {
+            // First, try to get the results from the cache
+            let key = CacheKey::new(self.name(), query, start, &CONFIG.search.locale);
+            let (page, source, revalidate) = match mode {
+                CacheMode::Offline => match RESPONSE_CACHE.lookup(&key).await {
+                    Some(page) => {
+                        RESPONSE_CACHE.record(true);
+                        match RESPONSE_CACHE.freshness(&page) {
+                            Freshness::Fresh | Freshness::Expiring => {
+                                (page, PageSource::Cache, false)
+                            }
+                            Freshness::Stale | Freshness::Expired => {
+                                (page, PageSource::Stale, false)
+                            }
+                        }
+                    }
+                    None => {
+                        RESPONSE_CACHE.record(false);
+                        return Err(anyhow::anyhow!(SearchError::NotCached));
+                    }
+                },
+                CacheMode::Normal => {
+                    let cached = RESPONSE_CACHE.lookup(&key).await;
+                    let cached = cached.map(|page| (RESPONSE_CACHE.freshness(&page), page));
+                    RESPONSE_CACHE.record(matches!(
+                        cached,
+                        Some((Freshness::Fresh | Freshness::Expiring | Freshness::Stale, _))
+                    ));
+                    match cached {
+                        Some((Freshness::Fresh, page)) => (page, PageSource::Cache, false),
+                        Some((Freshness::Expiring, page)) => (page, PageSource::Cache, true),
+                        Some((Freshness::Stale, page)) => (page, PageSource::Stale, true),
+                        Some((Freshness::Expired, _)) | None => (
+                            fetch_page(self, query, start, key).await?,
+                            PageSource::Network,
+                            false,
+                        ),
+                    }
+                }
+                CacheMode::Refresh => (
+                    fetch_page(self, query, start, key).await?,
+                    PageSource::Network,
+                    false,
+                ),
+            };
+
+            if page.results.is_empty() {
+                return Err(anyhow::anyhow!(SearchError::NoResults));
+            }
+            Ok(SearchPage {
+                results: page.results,
+                source,
+                revalidate,
+            })
+        }
+    }
 }
 
 /// Google search engine
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Google;
 
 impl Engine for Google {
@@ -74,55 +355,43 @@ impl Engine for Google {
         "Google"
     }
 
+    /// Get the base URL of the search engine
+    fn base_url(&self) -> &'static str {
+        &CONFIG.engine.base_urls.google
+    }
+
     /// Build the URL for a search query
     fn build_url(&self, query: &str, start: u16) -> String {
         format!(
-            "https://www.google.com/search?q={}&num=10&start={}",
+            "{}/search?q={}&num=10&start={}",
+            self.base_url().trim_end_matches('/'),
             urlencoding::encode(query),
             start
         )
     }
 
-    /// Perform a search
-    async fn search(&self, query: &str, start: u16) -> Result<Vec<SearchResult>> {
-        let url = self.build_url(query, start);
-        let text = fetch_response_text(&url).await?;
-        let document = scraper::Html::parse_document(&text);
-
+    /// Parse a Google results page
+    fn parse(&self, document: &scraper::Html) -> Result<Vec<SearchResult>> {
         let container_selector = scraper::Selector::parse("div.MjjYud").unwrap();
         let title_selector = scraper::Selector::parse("h3.LC20lb").unwrap();
         let link_selector = scraper::Selector::parse("a[jsname='UWckNb']").unwrap();
         let description_selector = scraper::Selector::parse("div.VwiC3b").unwrap();
 
-        let mut results = Vec::new();
-        let mut seen_urls = std::collections::HashSet::new();
-
-        for container in document.select(&container_selector) {
-            if let Some(link_element) = container.select(&link_selector).next() {
-                if let Some(href) = link_element.value().attr("href") {
-                    if href.starts_with("http") && !seen_urls.contains(href) {
-                        let title = container
-                            .select(&title_selector)
-                            .next()
-                            .map(|el| el.text().collect::<String>().trim().to_string())
-                            .unwrap_or_else(|| "No title".to_string());
-
-                        let description = container
-                            .select(&description_selector)
-                            .next()
-                            .map(|el| el.text().collect::<String>().trim().to_string())
-                            .unwrap_or_else(|| "No description".to_string());
-
-                        seen_urls.insert(href.to_string());
-                        results.push(SearchResult {
-                            title,
-                            url: href.to_string(),
-                            description,
-                        });
-                    }
+        let results = extract_results(
+            document,
+            &container_selector,
+            &title_selector,
+            &link_selector,
+            &description_selector,
+            |href| {
+                // Links from the basic HTML version are wrapped as /url?q=<target>
+                if href.starts_with("/url?") {
+                    query_param("https://www.google.com", href, "q")
+                } else {
+                    Some(href.to_string())
                 }
-            }
-         }
+            },
+        );
 
         if results.is_empty() {
             return Err(anyhow::anyhow!(SearchError::NoResults));
@@ -130,10 +399,53 @@ impl Engine for Google {
 
         Ok(results)
     }
+
+    /// Detect Google's /sorry/ redirect and reCAPTCHA page
+    fn detect_block(&self, url: &str, document: &scraper::Html) -> Option<String> {
+        if url_path(url).contains("/sorry/") {
+            return Some("redirected to Google's /sorry/ CAPTCHA page".to_string());
+        }
+        let captcha_selector =
+            scraper::Selector::parse("form#captcha-form, div.g-recaptcha").unwrap();
+        document
+            .select(&captcha_selector)
+            .next()
+            .map(|_| "Google reCAPTCHA challenge for unusual traffic".to_string())
+    }
+
+    /// Detect the redirect to consent.google.com shown on EU networks
+    fn detect_consent(&self, url: &str, document: &scraper::Html) -> bool {
+        if Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.starts_with("consent.")))
+            .unwrap_or(false)
+        {
+            return true;
+        }
+        let consent_selector = scraper::Selector::parse("form[action*='consent.google.']").unwrap();
+        document.select(&consent_selector).next().is_some()
+    }
+
+    /// Google's autocomplete endpoint, asked for the OpenSearch JSON format
+    fn suggest_url(&self, query: &str) -> Option<String> {
+        Some(format!(
+            "{}/complete/search?client=firefox&ie=utf-8&oe=utf-8&q={}",
+            self.base_url().trim_end_matches('/'),
+            urlencoding::encode(query)
+        ))
+    }
+
+    /// SOCS records the consent choice; CONSENT is its older equivalent
+    fn consent_cookies(&self) -> &'static [&'static str] {
+        &[
+            "SOCS=CAESEwgDEgk0ODE3Nzk3MjQaAmVuIAEaBgiA_LyaBg; Path=/",
+            "CONSENT=YES+; Path=/",
+        ]
+    }
 }
 
 /// Bing search engine
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Bing;
 
 impl Engine for Bing {
@@ -142,55 +454,48 @@ impl Engine for Bing {
         "Bing"
     }
 
+    /// Get the base URL of the search engine
+    fn base_url(&self) -> &'static str {
+        &CONFIG.engine.base_urls.bing
+    }
+
     /// Build the URL for a search query
     fn build_url(&self, query: &str, start: u16) -> String {
         format!(
-            "https://www.bing.com/search?q={}&count=10&first={}",
+            "{}/search?q={}&count=10&first={}",
+            self.base_url().trim_end_matches('/'),
             urlencoding::encode(query),
             start
         )
     }
 
-    /// Perform a search
-    async fn search(&self, query: &str, start: u16) -> Result<Vec<SearchResult>> {
-        let url = self.build_url(query, start);
-        let text = fetch_response_text(&url).await?;
-        let document = scraper::Html::parse_document(&text);
-
+    /// Parse a Bing results page
+    fn parse(&self, document: &scraper::Html) -> Result<Vec<SearchResult>> {
         let container_selector = scraper::Selector::parse("li.b_algo").unwrap();
         let title_selector = scraper::Selector::parse("h2").unwrap();
         let link_selector = scraper::Selector::parse("h2 a").unwrap();
         let description_selector = scraper::Selector::parse("div.b_caption p").unwrap();
 
-        let mut results = Vec::new();
-        let mut seen_urls = std::collections::HashSet::new();
-
-        for container in document.select(&container_selector) {
-            if let Some(link_element) = container.select(&link_selector).next() {
-                if let Some(href) = link_element.value().attr("href") {
-                    if href.starts_with("http") && !seen_urls.contains(href) {
-                        let title = container
-                            .select(&title_selector)
-                            .next()
-                            .map(|el| el.text().collect::<String>().trim().to_string())
-                            .unwrap_or_else(|| "No title".to_string());
-
-                        let description = container
-                            .select(&description_selector)
-                            .next()
-                            .map(|el| el.text().collect::<String>().trim().to_string())
-                            .unwrap_or_else(|| "No description".to_string());
-
-                        seen_urls.insert(href.to_string());
-                        results.push(SearchResult {
-                            title,
-                            url: href.to_string(),
-                            description,
-                        });
-                    }
+        let results = extract_results(
+            document,
+            &container_selector,
+            &title_selector,
+            &link_selector,
+            &description_selector,
+            |href| {
+                // Tracking links look like /ck/a?...&u=a1<base64url target>&...
+                if href.contains("/ck/a?") {
+                    query_param("https://www.bing.com", href, "u")
+                        .and_then(|u| u.strip_prefix("a1").map(str::to_string))
+                        .and_then(|encoded| {
+                            URL_SAFE_NO_PAD.decode(encoded.trim_end_matches('=')).ok()
+                        })
+                        .and_then(|bytes| String::from_utf8(bytes).ok())
+                } else {
+                    Some(href.to_string())
                 }
-            }
-        }
+            },
+        );
 
         if results.is_empty() {
             return Err(anyhow::anyhow!(SearchError::NoResults));
@@ -198,10 +503,46 @@ impl Engine for Bing {
 
         Ok(results)
     }
+
+    /// Detect Bing's challenge page
+    fn detect_block(&self, url: &str, document: &scraper::Html) -> Option<String> {
+        if url_path(url).contains("/challenge") {
+            return Some("redirected to Bing's challenge page".to_string());
+        }
+        let challenge_selector =
+            scraper::Selector::parse("form#b_captcha, iframe[src*='challenges.cloudflare.com']")
+                .unwrap();
+        document
+            .select(&challenge_selector)
+            .next()
+            .map(|_| "Bing challenge page asking to verify you are human".to_string())
+    }
+
+    /// Detect the cookie banner page served instead of results on EU networks
+    fn detect_consent(&self, _url: &str, document: &scraper::Html) -> bool {
+        let banner_selector = scraper::Selector::parse("div#bnp_container").unwrap();
+        let result_selector = scraper::Selector::parse("li.b_algo").unwrap();
+        document.select(&banner_selector).next().is_some()
+            && document.select(&result_selector).next().is_none()
+    }
+
+    /// Bing's OpenSearch autocomplete endpoint
+    fn suggest_url(&self, query: &str) -> Option<String> {
+        Some(format!(
+            "{}/osjson.aspx?query={}",
+            self.base_url().trim_end_matches('/'),
+            urlencoding::encode(query)
+        ))
+    }
+
+    /// BCP records the consent choice
+    fn consent_cookies(&self) -> &'static [&'static str] {
+        &["BCP=AD=1&AL=1&SM=1; Path=/"]
+    }
 }
 
 /// DuckDuckGo search engine
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DuckDuckGo;
 
 impl Engine for DuckDuckGo {
@@ -210,21 +551,23 @@ impl Engine for DuckDuckGo {
         "DuckDuckGo"
     }
 
+    /// Get the base URL of the search engine
+    fn base_url(&self) -> &'static str {
+        &CONFIG.engine.base_urls.duckduckgo
+    }
+
     /// Build the URL for a search query
     fn build_url(&self, query: &str, start: u16) -> String {
         format!(
-            "https://html.duckduckgo.com/html/?q={}&s={}",
+            "{}/html/?q={}&s={}",
+            self.base_url().trim_end_matches('/'),
             urlencoding::encode(query),
             start
         )
     }
 
-    /// Perform a search
-    async fn search(&self, query: &str, start: u16) -> Result<Vec<SearchResult>> {
-        let url = self.build_url(query, start);
-        let text = fetch_response_text(&url).await?;
-        let document = scraper::Html::parse_document(&text);
-
+    /// Parse a DuckDuckGo HTML results page
+    fn parse(&self, document: &scraper::Html) -> Result<Vec<SearchResult>> {
         let result_selector = scraper::Selector::parse(".result").unwrap();
         let title_selector = scraper::Selector::parse(".result__title a").unwrap();
         let snippet_selector = scraper::Selector::parse(".result__snippet").unwrap();
@@ -240,21 +583,17 @@ impl Engine for DuckDuckGo {
                 if let Some(url) = title_elem.value().attr("href") {
                     let title = title_elem.text().collect::<String>();
                     let description = snippet_elem.text().collect::<String>();
-                    
-                    let clean_url = if url.starts_with("http") {
+
+                    // Redirect links look like //duckduckgo.com/l/?uddg=<target>&rut=...
+                    let clean_url = if url.contains("uddg=") {
+                        match query_param("https://duckduckgo.com", url, "uddg") {
+                            Some(target) => target,
+                            None => continue,
+                        }
+                    } else if url.starts_with("http") {
                         url.to_string()
                     } else if url.starts_with("//") {
                         format!("https:{}", url)
-                    } else if url.starts_with('/') {
-                        if let Some(actual_url) = url.split("uddg=").nth(1) {
-                            if let Ok(decoded) = urlencoding::decode(actual_url) {
-                                decoded.into_owned()
-                            } else {
-                                continue;
-                            }
-                        } else {
-                            continue;
-                        }
                     } else {
                         continue;
                     };
@@ -279,123 +618,282 @@ impl Engine for DuckDuckGo {
 
         Ok(results)
     }
-}  
 
-static RESPONSE_CACHE: Lazy<Cache<String, String>> = Lazy::new(|| {
-    Cache::builder()
-        .max_capacity(CONFIG.cache.max_capacity)
-        .time_to_live(Duration::from_secs(CONFIG.cache.time_to_live)) // 10 minutes expiration
-        .build()
-});
+    /// DuckDuckGo's autocomplete endpoint, which lives on the main host
+    fn suggest_url(&self, query: &str) -> Option<String> {
+        Some(format!(
+            "{}/ac/?type=list&q={}",
+            CONFIG
+                .engine
+                .base_urls
+                .duckduckgo_suggest
+                .trim_end_matches('/'),
+            urlencoding::encode(query)
+        ))
+    }
 
-/// Fetch the response text from a URL
-async fn fetch_response_text(url: &str) -> Result<String> {
-    // First, try to get the response from the cache
-    if let Some(cached_response) = RESPONSE_CACHE.get(url).await {
-        return Ok(cached_response);
+    /// Detect DuckDuckGo's anomaly (bot challenge) page
+    fn detect_block(&self, _url: &str, document: &scraper::Html) -> Option<String> {
+        let anomaly_selector = scraper::Selector::parse(
+            "[data-testid='anomaly-modal'], div.anomaly-modal__modal, form[action*='anomaly.js']",
+        )
+        .unwrap();
+        document
+            .select(&anomaly_selector)
+            .next()
+            .map(|_| "DuckDuckGo anomaly page with a bot challenge".to_string())
     }
+}
 
-    let mut rng = thread_rng();
+/// Extract results from container elements holding a title, a link and a description
+///
+/// `resolve` turns the raw `href` into the target URL, returning `None` to skip the result.
+fn extract_results(
+    document: &scraper::Html,
+    container_selector: &scraper::Selector,
+    title_selector: &scraper::Selector,
+    link_selector: &scraper::Selector,
+    description_selector: &scraper::Selector,
+    resolve: impl Fn(&str) -> Option<String>,
+) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    let mut seen_urls = std::collections::HashSet::new();
+
+    for container in document.select(container_selector) {
+        let Some(href) = container
+            .select(link_selector)
+            .next()
+            .and_then(|link| link.value().attr("href"))
+        else {
+            continue;
+        };
+        let Some(url) = resolve(href) else {
+            continue;
+        };
+        if !url.starts_with("http") || seen_urls.contains(&url) {
+            continue;
+        }
 
-    // Randomly select a User-Agent
-    let user_agent = CONFIG.search.user_agents.choose(&mut rng).unwrap_or(&CONFIG.search.user_agents[0]);
+        let title = container
+            .select(title_selector)
+            .next()
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| "No title".to_string());
+
+        let description = container
+            .select(description_selector)
+            .next()
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| "No description".to_string());
+
+        seen_urls.insert(url.clone());
+        results.push(SearchResult {
+            title,
+            url,
+            description,
+        });
+    }
+
+    results
+}
+
+/// Parse an autocomplete response in the OpenSearch suggestions format,
+/// `["query", ["suggestion", ...], ...]`
+pub fn parse_suggestions(body: &str) -> Result<Vec<String>> {
+    let value: serde_json::Value = serde_json::from_str(body).map_err(|e| {
+        SearchError::InvalidResponse(format!("Autocomplete response is not JSON: {}", e))
+    })?;
+    let suggestions = value
+        .get(1)
+        .and_then(|suggestions| suggestions.as_array())
+        .ok_or_else(|| {
+            SearchError::InvalidResponse("Autocomplete response has no suggestions".to_string())
+        })?;
+    Ok(suggestions
+        .iter()
+        .filter_map(|suggestion| suggestion.as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Get a query parameter from a possibly relative link
+fn query_param(base: &str, href: &str, name: &str) -> Option<String> {
+    let url = Url::parse(base).ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Path of a URL, or an empty string if it can't be parsed
+fn url_path(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_default()
+}
 
-    // Add a random delay
-    let jitter = rng.gen_range(0..CONFIG.search.max_jitter);
-    sleep(Duration::from_millis(CONFIG.search.base_delay + jitter)).await;
+/// Transport shared by all engines, selected by the `[recording]` settings
+static TRANSPORT: Lazy<Transport> = Lazy::new(|| Transport::from_config(&CONFIG.recording));
 
-    let client = reqwest::Client::builder()
-        .user_agent(user_agent.to_string())
-        .cookie_store(true)
-        .timeout(Duration::from_secs(CONFIG.search.request_timeout))
+/// Parsed results shared by all engines, in memory and optionally on disk
+pub static RESPONSE_CACHE: Lazy<ResponseCache> =
+    Lazy::new(|| ResponseCache::from_config(&CONFIG.cache));
+
+/// Autocomplete suggestions of all engines, by engine and partial query
+static SUGGESTION_CACHE: Lazy<Cache<CacheKey, Vec<String>>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(1000)
+        .time_to_live(Duration::from_secs(CONFIG.cache.time_to_live))
         .build()
-        .map_err(|e| SearchError::Other(format!("Failed to build client: {}", e)))?;
+});
 
-    let mut last_error = None;
+/// Retry policy shared by all engines, from `[search] max_retries` and `[retry]`
+static RETRY_POLICY: Lazy<RetryPolicy> =
+    Lazy::new(|| RetryPolicy::from_config(CONFIG.search.max_retries, &CONFIG.retry));
+
+/// Fetch and parse a results page from the network, storing it in the cache
+async fn fetch_page<E: Engine + ?Sized>(
+    engine: &E,
+    query: &str,
+    start: u16,
+    key: CacheKey,
+) -> Result<CachedPage> {
+    let url = engine.build_url(query, start);
+    let FetchedPage { results, text } = fetch_results(engine, &url).await?;
+    // Pages without results are cached too, as an empty list
+    let results = match results {
+        Ok(results) => results,
+        Err(e) if matches!(e.downcast_ref(), Some(SearchError::NoResults)) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let page = CachedPage::new(results, RESPONSE_CACHE.keeps_html().then_some(text));
+    RESPONSE_CACHE.insert(key, page.clone()).await;
+    Ok(page)
+}
 
-    // Retry mechanism
-    for retry in 0..CONFIG.search.max_retries {
-        if retry > 0 {
-            // If it's a retry, increase the delay time
-            sleep(Duration::from_millis(
-                (CONFIG.search.base_delay * (retry as u64)) + jitter,
-            ))
-            .await;
-        }
+/// Results page read from a response
+struct FetchedPage {
+    /// Results parsed from the page
+    results: Result<Vec<SearchResult>>,
+    /// Text of the page
+    text: String,
+}
 
-        // Use the timeout package to wrap the entire request process
-        match timeout(
-            Duration::from_secs(CONFIG.search.request_timeout),
-            async {
-                match client
-                    .get(url)
-                    .header(
-                        "Accept",
-                        "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
-                    )
-                    .header("Accept-Language", "en-US,en;q=0.9")
-                    .header("Accept-Encoding", "identity")
-                    .send()
-                    .await
-                {
-                    Ok(response) => {
-                        let status = response.status();
-                        
-                        match status.as_u16() {
-                            200 => {
-                                // Use the timeout package to wrap the response body reading
-                                match timeout(
-                                    Duration::from_secs(CONFIG.search.response_timeout),
-                                    response.text()
-                                ).await {
-                                    Ok(Ok(text)) => {
-                                        if text.contains("detected unusual traffic") 
-                                           || text.contains("CAPTCHA") 
-                                           || text.contains("blocked") {
-                                            Err(SearchError::Blocked)
-                                        } else {
-                                            Ok(text)
-                                        }
-                                    }
-                                    Ok(Err(e)) => {
-                                        Err(SearchError::InvalidResponse(e.to_string()))
-                                    }
-                                    Err(_) => {
-                                        Err(SearchError::Timeout)
-                                    }
-                                }
-                            }
-                            429 => Err(SearchError::RateLimited),
-                            403 => Err(SearchError::Blocked),
-                            408 | 504 => Err(SearchError::Timeout),
-                            _ => Err(SearchError::NetworkError(
-                                format!("HTTP error: {}", status)
-                            )),
-                        }
-                    }
-                    Err(e) => {
-                        if e.is_timeout() {
-                            Err(SearchError::Timeout)
-                        } else if e.is_connect() {
-                            Err(SearchError::NetworkError(
-                                "Connection failed".to_string()
-                            ))
-                        } else {
-                            Err(SearchError::NetworkError(e.to_string()))
-                        }
-                    }
+/// Fetch a results page from a URL on behalf of an engine
+async fn fetch_results<E: Engine + ?Sized>(engine: &E, url: &str) -> Result<FetchedPage> {
+    // Randomly select a User-Agent
+    let user_agent = CONFIG
+        .search
+        .user_agents
+        .choose(&mut thread_rng())
+        .unwrap_or(&CONFIG.search.user_agents[0]);
+
+    let policy = &*RETRY_POLICY;
+    let started = Instant::now();
+    let mut attempts = 0;
+    let mut consented = false;
+
+    // Retry mechanism
+    loop {
+        attempts += 1;
+
+        // Stay under the engine's request rate; only network requests count
+        RATE_LIMITER.acquire(engine.name()).await;
+
+        // Use the timeout package to wrap the entire request process, without
+        // running past the overall deadline
+        let request_timeout =
+            Duration::from_secs(CONFIG.search.request_timeout).min(policy.remaining(started));
+        let result = match timeout(
+            request_timeout,
+            TRANSPORT.get(engine.name(), url, user_agent),
+        )
+        .await
+        {
+            Ok(Ok(response)) => match read_response(engine, response, !consented) {
+                Some(result) => result,
+                None => {
+                    // Accept the consent interstitial once and ask again; the cookies
+                    // stay in the engine's jar, so this isn't counted as an attempt
+                    TRANSPORT
+                        .add_cookies(engine.name(), engine.base_url(), engine.consent_cookies())
+                        .await;
+                    consented = true;
+                    attempts -= 1;
+                    continue;
                 }
-            }
-        ).await {
-            Ok(Ok(text)) => {
-                // Store the response in the cache
-                RESPONSE_CACHE.insert(url.to_string(), text.clone()).await;
-                return Ok(text);
-            }
-            Ok(Err(e)) => last_error = Some(e),
-            Err(_) => last_error = Some(SearchError::Timeout),
+            },
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(SearchError::Timeout),
+        };
+
+        let e = match result {
+            Ok(page) => return Ok(page),
+            Err(e) => e,
+        };
+        // The RNG isn't Send, so it must be gone before sleeping
+        let delay = policy.next_delay(attempts, started, &e, &mut thread_rng());
+        match delay {
+            Some(delay) => sleep(delay).await,
+            None => return Err(anyhow::anyhow!(e)),
         }
     }
+}
+
+/// Parse a response once, then check it and extract its results
+///
+/// Returns `None` for a consent interstitial when `accept_consent` is set,
+/// so the caller can accept it and ask again. The document isn't `Send`, so
+/// it is never kept across an await.
+fn read_response<E: Engine + ?Sized>(
+    engine: &E,
+    response: HttpResponse,
+    accept_consent: bool,
+) -> Option<Result<FetchedPage, SearchError>> {
+    let document = scraper::Html::parse_document(&response.body);
+    if accept_consent && engine.detect_consent(&response.url, &document) {
+        return None;
+    }
+    Some(
+        check_response(engine, response, &document).map(|text| FetchedPage {
+            results: engine.parse(&document),
+            text,
+        }),
+    )
+}
 
-    Err(anyhow::anyhow!(last_error.unwrap_or(SearchError::Unknown)))
+/// Turn an HTTP response into the page text or the matching search error
+///
+/// Block pages are recognized first, as engines serve them with 200 as well
+/// as 403 or 429 status codes. A consent interstitial reaching this point
+/// persisted despite the consent cookies.
+fn check_response<E: Engine + ?Sized>(
+    engine: &E,
+    response: HttpResponse,
+    document: &scraper::Html,
+) -> Result<String, SearchError> {
+    if let Some(reason) = engine.detect_block(&response.url, document) {
+        return Err(SearchError::Blocked { reason });
+    }
+    if engine.detect_consent(&response.url, document) {
+        return Err(SearchError::Blocked {
+            reason: format!("{} cookie consent page was not accepted", engine.name()),
+        });
+    }
+
+    match response.status {
+        200 => Ok(response.body),
+        429 => Err(SearchError::RateLimited {
+            retry_after: response.retry_after(),
+        }),
+        403 => Err(SearchError::Blocked {
+            reason: "HTTP 403 Forbidden".to_string(),
+        }),
+        408 | 504 => Err(SearchError::Timeout),
+        status => Err(SearchError::NetworkError(format!(
+            "HTTP error: {}",
+            reqwest::StatusCode::from_u16(status)
+                .map(|status| status.to_string())
+                .unwrap_or_else(|_| status.to_string())
+        ))),
+    }
 }
@@ -1,2 +1,6 @@
+pub mod cache;
 pub mod engine;
 pub mod models;
+pub mod rate_limit;
+pub mod retry;
+pub mod transport;
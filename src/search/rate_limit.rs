@@ -0,0 +1,117 @@
+use once_cell::sync::Lazy;
+use rand::{thread_rng, Rng};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+use crate::{core::config::RateLimitConfig, CONFIG};
+
+/// Process-wide rate limiter shared by every search, page load and engine switch
+pub static RATE_LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(&CONFIG.rate_limit, CONFIG.search.max_jitter));
+
+/// Token bucket allowing `capacity` requests at once, refilled at a steady rate
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(capacity: u32, requests_per_minute: u32, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_sec: requests_per_minute as f64 / 60.0,
+            tokens: capacity as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Tokens available right now, negative while reservations are pending
+    pub fn available(&mut self, now: Instant) -> f64 {
+        self.refill(now);
+        self.tokens
+    }
+
+    /// Reserve a token, returning how long to wait before using it
+    ///
+    /// Reservations may drive the bucket into debt, so concurrent callers
+    /// are spaced out in the order they asked.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.refill_per_sec)
+        }
+    }
+}
+
+/// Token buckets keyed by engine
+#[derive(Debug)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    max_jitter: u64,
+}
+
+impl RateLimiter {
+    /// Create a limiter with one bucket per engine, adding up to
+    /// `max_jitter` milliseconds to throttled requests
+    pub fn new(config: &RateLimitConfig, max_jitter: u64) -> Self {
+        let now = Instant::now();
+        let buckets = ["google", "bing", "duckduckgo"]
+            .iter()
+            .map(|engine| {
+                let limit = config.for_engine(engine);
+                (
+                    engine.to_string(),
+                    TokenBucket::new(limit.burst, limit.requests_per_minute, now),
+                )
+            })
+            .collect();
+
+        Self {
+            buckets: Mutex::new(buckets),
+            max_jitter,
+        }
+    }
+
+    /// Wait until the engine's bucket allows another request
+    ///
+    /// Requests within the burst go out immediately; throttled ones also get
+    /// a random jitter so they don't follow a fixed rhythm.
+    pub async fn acquire(&self, engine: &str) {
+        let wait = match self.buckets.lock().unwrap().get_mut(&engine.to_lowercase()) {
+            Some(bucket) => bucket.reserve(Instant::now()),
+            None => Duration::ZERO,
+        };
+        if !wait.is_zero() {
+            let jitter = thread_rng().gen_range(0..=self.max_jitter);
+            sleep(wait + Duration::from_millis(jitter)).await;
+        }
+    }
+
+    /// Whether the engine has a token to spare beyond its next request
+    ///
+    /// Speculative requests such as prefetches only go out then, so they
+    /// never delay a search the user asked for.
+    pub fn has_spare(&self, engine: &str) -> bool {
+        match self.buckets.lock().unwrap().get_mut(&engine.to_lowercase()) {
+            Some(bucket) => bucket.available(Instant::now()) >= 2.0,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+use crate::{core::config::RetryConfig, SearchError};
+
+/// Decides whether and when a failed request is attempted again
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Error kinds worth retrying, see [`SearchError::kind`]
+    pub retry_on: Vec<String>,
+    /// Backoff ceiling for the first retry, doubled on every retry
+    pub backoff_base: Duration,
+    /// Upper bound for a single backoff
+    pub backoff_max: Duration,
+    /// Total time allowed for all attempts
+    pub deadline: Duration,
+    /// Wait as long as the engine asks in a `Retry-After` header
+    pub respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// Create a policy from the `[retry]` settings and the attempt limit
+    pub fn from_config(max_attempts: u32, config: &RetryConfig) -> Self {
+        Self {
+            max_attempts,
+            retry_on: config.retry_on.clone(),
+            backoff_base: Duration::from_millis(config.backoff_base),
+            backoff_max: Duration::from_millis(config.backoff_max),
+            deadline: Duration::from_secs(config.deadline),
+            respect_retry_after: config.respect_retry_after,
+        }
+    }
+
+    /// Whether an error of this kind may succeed on another attempt
+    pub fn is_retryable(&self, error: &SearchError) -> bool {
+        self.retry_on.iter().any(|kind| kind == error.kind())
+    }
+
+    /// Backoff ceiling before retry number `retry` (1-based): `base * 2^(retry - 1)`, capped
+    pub fn backoff_ceiling(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.backoff_base
+            .checked_mul(factor)
+            .unwrap_or(self.backoff_max)
+            .min(self.backoff_max)
+    }
+
+    /// Delay before retry number `retry` using full jitter, or the engine's
+    /// `Retry-After` if it asked for longer
+    pub fn delay(&self, retry: u32, error: &SearchError, rng: &mut impl Rng) -> Duration {
+        let ceiling = self.backoff_ceiling(retry).as_millis() as u64;
+        let backoff = Duration::from_millis(rng.gen_range(0..=ceiling));
+
+        match error {
+            SearchError::RateLimited {
+                retry_after: Some(retry_after),
+            } if self.respect_retry_after => backoff.max(*retry_after),
+            _ => backoff,
+        }
+    }
+
+    /// Decide whether to retry after `attempts` failed attempts started at
+    /// `started`, returning the delay to wait first
+    pub fn next_delay(
+        &self,
+        attempts: u32,
+        started: Instant,
+        error: &SearchError,
+        rng: &mut impl Rng,
+    ) -> Option<Duration> {
+        if attempts >= self.max_attempts || !self.is_retryable(error) {
+            return None;
+        }
+
+        let delay = self.delay(attempts, error, rng);
+        if started.elapsed() + delay >= self.deadline {
+            return None;
+        }
+        Some(delay)
+    }
+
+    /// Time left before the deadline of a request started at `started`
+    pub fn remaining(&self, started: Instant) -> Duration {
+        self.deadline.saturating_sub(started.elapsed())
+    }
+}
@@ -0,0 +1,467 @@
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+use tokio::time::timeout;
+
+use crate::{
+    core::config::{CookieConfig, RecordingConfig},
+    SearchError, CONFIG,
+};
+
+/// Raw HTTP response as seen by the search engines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    /// Final URL after following redirects
+    pub url: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers, lowercased names
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Response body
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Get the first value of a header by (case-insensitive) name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Delay requested by a `Retry-After` header, in seconds or as an HTTP date
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.header("retry-after")?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        httpdate::parse_http_date(value)
+            .ok()
+            .map(|date| date.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+}
+
+/// A recorded request/response pair as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    url: String,
+    response: HttpResponse,
+}
+
+/// HTTP transport used by all search engines
+///
+/// `Live` talks to the network, `Record` does the same and additionally saves
+/// every exchange to a directory, and `Replay` answers only from such a
+/// directory, which makes full search flows deterministic without network.
+#[derive(Debug)]
+pub enum Transport {
+    Live(LiveTransport),
+    Record(LiveTransport, PathBuf),
+    Replay(PathBuf),
+}
+
+impl Transport {
+    /// Create the transport selected in the configuration
+    pub fn from_config(config: &RecordingConfig) -> Self {
+        match config.mode.as_str() {
+            "record" => Transport::Record(LiveTransport::default(), config.dir.clone()),
+            "replay" => Transport::Replay(config.dir.clone()),
+            _ => Transport::Live(LiveTransport::default()),
+        }
+    }
+
+    /// Send a GET request on behalf of an engine
+    pub async fn get(
+        &self,
+        engine: &str,
+        url: &str,
+        user_agent: &str,
+    ) -> Result<HttpResponse, SearchError> {
+        match self {
+            Transport::Live(live) => live.get(engine, url, user_agent).await,
+            Transport::Record(live, dir) => {
+                let response = live.get(engine, url, user_agent).await?;
+                save_recording(dir, url, &response)?;
+                Ok(response)
+            }
+            Transport::Replay(dir) => load_recording(dir, url),
+        }
+    }
+
+    /// Store cookies for an engine, to be sent with its requests to `url`
+    ///
+    /// Cookies use the `Set-Cookie` syntax. They are ignored in replay mode.
+    pub async fn add_cookies(&self, engine: &str, url: &str, cookies: &[&str]) {
+        let (Transport::Live(live) | Transport::Record(live, _)) = self else {
+            return;
+        };
+        let Ok(url) = reqwest::Url::parse(url) else {
+            return;
+        };
+        let jar = live.cookie_jar(engine);
+        {
+            let mut store = jar.store.lock().unwrap();
+            for cookie in cookies {
+                // Invalid cookies are skipped, like the ones a server sends
+                let _ = store.parse(cookie, &url);
+            }
+        }
+        live.save_cookies(engine).await;
+    }
+}
+
+/// Transport that sends requests over the network
+///
+/// Clients are kept for the lifetime of the process, one per engine and
+/// user agent, so connections and negotiated HTTP/2 sessions are reused
+/// between searches. Cookies are kept per engine and shared by its clients;
+/// with `[cookies] persist` they are also saved to disk whenever a response
+/// changes them and loaded again in the next session.
+#[derive(Debug, Default)]
+pub struct LiveTransport {
+    clients: Mutex<HashMap<(String, String), reqwest::Client>>,
+    cookie_jars: Mutex<HashMap<String, Arc<CookieJar>>>,
+}
+
+/// Cookies of an engine, shared by its clients
+#[derive(Debug)]
+struct CookieJar {
+    store: Arc<CookieStoreMutex>,
+    /// Encoded store as last loaded or saved, locked while saving so the
+    /// saves of an engine land in order
+    saved: tokio::sync::Mutex<Vec<u8>>,
+}
+
+impl LiveTransport {
+    /// Get the cookie jar of an engine, loading it from disk on first use
+    fn cookie_jar(&self, engine: &str) -> Arc<CookieJar> {
+        self.cookie_jars
+            .lock()
+            .unwrap()
+            .entry(engine.to_string())
+            .or_insert_with(|| {
+                let store = if CONFIG.cookies.persist {
+                    load_cookies(&CONFIG.cookies, engine)
+                } else {
+                    CookieStore::default()
+                };
+                Arc::new(CookieJar {
+                    saved: tokio::sync::Mutex::new(encode_cookies(&store).unwrap_or_default()),
+                    store: Arc::new(CookieStoreMutex::new(store)),
+                })
+            })
+            .clone()
+    }
+
+    /// Save the cookie jar of an engine if cookies are persisted and the
+    /// jar changed since it was last loaded or saved
+    ///
+    /// The file is written on the blocking thread pool. Failing to save only
+    /// costs the cookies of this session, so errors are ignored.
+    async fn save_cookies(&self, engine: &str) {
+        if !CONFIG.cookies.persist {
+            return;
+        }
+        let jar = self.cookie_jar(engine);
+        let mut saved = jar.saved.lock().await;
+        let Ok(json) = encode_cookies(&jar.store.lock().unwrap()) else {
+            return;
+        };
+        if *saved == json {
+            return;
+        }
+        let engine = engine.to_string();
+        let written = tokio::task::spawn_blocking(move || {
+            write_cookies(&CONFIG.cookies, &engine, &json).map(|()| json)
+        })
+        .await;
+        if let Ok(Ok(json)) = written {
+            *saved = json;
+        }
+    }
+
+    /// Get the pooled client for an engine and user agent, creating it on first use
+    fn client(&self, engine: &str, user_agent: &str) -> Result<reqwest::Client, SearchError> {
+        let mut clients = self.clients.lock().unwrap();
+        let key = (engine.to_string(), user_agent.to_string());
+        if let Some(client) = clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        // gzip, brotli and deflate decoding are enabled through crate features
+        let builder = reqwest::Client::builder()
+            .user_agent(user_agent.to_string())
+            .cookie_provider(self.cookie_jar(engine).store.clone())
+            .timeout(Duration::from_secs(CONFIG.search.request_timeout))
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60));
+
+        let client = with_proxy(builder, engine)?
+            .build()
+            .map_err(|e| SearchError::Other(format!("Failed to build client: {}", e)))?;
+
+        clients.insert(key, client.clone());
+        Ok(client)
+    }
+
+    async fn get(
+        &self,
+        engine: &str,
+        url: &str,
+        user_agent: &str,
+    ) -> Result<HttpResponse, SearchError> {
+        let response = self
+            .client(engine, user_agent)?
+            .get(url)
+            .header(
+                "Accept",
+                "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
+            )
+            .header("Accept-Language", accept_language(&CONFIG.search.locale))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    SearchError::Timeout
+                } else if e.is_connect() {
+                    SearchError::NetworkError("Connection failed".to_string())
+                } else {
+                    SearchError::NetworkError(e.to_string())
+                }
+            })?;
+
+        self.save_cookies(engine).await;
+
+        let final_url = response.url().to_string();
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| (name.as_str().to_string(), value.to_string()))
+            })
+            .collect();
+
+        // Use the timeout package to wrap the response body reading
+        let body = match timeout(
+            Duration::from_secs(CONFIG.search.response_timeout),
+            response.text(),
+        )
+        .await
+        {
+            Ok(Ok(text)) => text,
+            Ok(Err(e)) => return Err(SearchError::InvalidResponse(e.to_string())),
+            Err(_) => return Err(SearchError::Timeout),
+        };
+
+        Ok(HttpResponse {
+            url: final_url,
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// `Accept-Language` value preferring a locale, then its language
+fn accept_language(locale: &str) -> String {
+    match locale.split_once('-') {
+        Some((language, _)) => format!("{},{};q=0.9", locale, language),
+        None => locale.to_string(),
+    }
+}
+
+/// Apply the configured proxy for an engine to a client builder
+///
+/// Without proxy settings reqwest falls back to the `HTTP_PROXY`,
+/// `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` environment variables.
+fn with_proxy(
+    builder: reqwest::ClientBuilder,
+    engine: &str,
+) -> Result<reqwest::ClientBuilder, SearchError> {
+    let Some(settings) = CONFIG.proxy.for_engine(engine) else {
+        return Ok(builder);
+    };
+    let Some(url) = settings.proxy_url() else {
+        return Ok(builder);
+    };
+    if url == "direct" {
+        return Ok(builder.no_proxy());
+    }
+
+    let mut proxy = reqwest::Proxy::all(url)
+        .map_err(|e| SearchError::Other(format!("Invalid proxy {}: {}", url, e)))?;
+    if let Some(username) = &settings.username {
+        proxy = proxy.basic_auth(username, settings.password.as_deref().unwrap_or_default());
+    }
+    let no_proxy = if CONFIG.proxy.no_proxy.is_empty() {
+        reqwest::NoProxy::from_env()
+    } else {
+        reqwest::NoProxy::from_string(&CONFIG.proxy.no_proxy.join(","))
+    };
+
+    Ok(builder.proxy(proxy.no_proxy(no_proxy)))
+}
+
+/// Path of the cookie file of an engine
+pub fn cookie_path(config: &CookieConfig, engine: &str) -> PathBuf {
+    config.dir.join(format!("{}.json", engine.to_lowercase()))
+}
+
+/// Load the saved cookies of an engine, or an empty jar if there are none
+///
+/// Session cookies are kept as well: engines tie their CAPTCHA checks to
+/// them, so losing them on restart makes CAPTCHAs more likely.
+pub fn load_cookies(config: &CookieConfig, engine: &str) -> CookieStore {
+    fs::File::open(cookie_path(config, engine))
+        .ok()
+        .and_then(|file| CookieStore::load_json_all(BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+/// Save the cookies of an engine, including session cookies
+///
+/// The file is only readable by the current user on unix, since the
+/// cookies identify the user to the engine.
+pub fn save_cookies(
+    config: &CookieConfig,
+    engine: &str,
+    store: &CookieStore,
+) -> Result<(), SearchError> {
+    write_cookies(config, engine, &encode_cookies(store)?)
+}
+
+fn encode_cookies(store: &CookieStore) -> Result<Vec<u8>, SearchError> {
+    let mut json = Vec::new();
+    store
+        .save_incl_expired_and_nonpersistent_json(&mut json)
+        .map_err(|e| SearchError::Other(format!("Failed to encode cookies: {}", e)))?;
+    Ok(json)
+}
+
+fn write_cookies(config: &CookieConfig, engine: &str, json: &[u8]) -> Result<(), SearchError> {
+    fs::create_dir_all(&config.dir)
+        .and_then(|_| write_private(&cookie_path(config, engine), json))
+        .map_err(|e| SearchError::Other(format!("Failed to save cookies: {}", e)))
+}
+
+/// Replace a file with `contents` readable only by the current user on unix
+///
+/// The contents go to a temporary file renamed over the old one, so a crash
+/// or another writer never leaves a partly written file behind.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let temp_path = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let written = options
+        .open(&temp_path)
+        .and_then(|mut file| file.write_all(contents))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Delete the saved cookies of all engines, returning the number of files removed
+pub fn clear_cookies(config: &CookieConfig) -> std::io::Result<usize> {
+    let entries = match fs::read_dir(&config.dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Path of the recording file for a URL
+///
+/// File names use a FNV-1a hash of the URL so they stay stable across
+/// platforms and Rust versions.
+pub fn recording_path(dir: &Path, url: &str) -> PathBuf {
+    let hash = fnv1a(url.as_bytes());
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string());
+    dir.join(format!("{}-{:016x}.json", host, hash))
+}
+
+/// FNV-1a hash, stable across platforms and Rust versions
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Headers left out of recordings, which are meant to be shared in bug
+/// reports and would otherwise carry the user's engine sessions
+const PRIVATE_HEADERS: [&str; 5] = [
+    "set-cookie",
+    "set-cookie2",
+    "cookie",
+    "authorization",
+    "proxy-authorization",
+];
+
+fn save_recording(dir: &Path, url: &str, response: &HttpResponse) -> Result<(), SearchError> {
+    let mut response = response.clone();
+    response
+        .headers
+        .retain(|(name, _)| !PRIVATE_HEADERS.contains(&name.to_lowercase().as_str()));
+    let recording = Recording {
+        url: url.to_string(),
+        response,
+    };
+    let json = serde_json::to_string_pretty(&recording)
+        .map_err(|e| SearchError::Other(format!("Failed to encode recording: {}", e)))?;
+    fs::create_dir_all(dir)
+        .and_then(|_| fs::write(recording_path(dir, url), json))
+        .map_err(|e| SearchError::Other(format!("Failed to save recording: {}", e)))
+}
+
+fn load_recording(dir: &Path, url: &str) -> Result<HttpResponse, SearchError> {
+    let path = recording_path(dir, url);
+    let content = fs::read_to_string(&path).map_err(|_| SearchError::RecordingMissing {
+        url: url.to_string(),
+        dir: dir.display().to_string(),
+    })?;
+    let recording: Recording = serde_json::from_str(&content).map_err(|e| {
+        SearchError::InvalidResponse(format!("Corrupt recording {}: {}", path.display(), e))
+    })?;
+    Ok(recording.response)
+}
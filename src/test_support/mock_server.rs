@@ -0,0 +1,427 @@
+//! Local HTTP server emulating the result pages of every search engine
+//!
+//! Each engine is served under its own path prefix (`/google`, `/bing`,
+//! `/duckduckgo`), so pointing `[engine.base_urls]` at
+//! [`MockServer::base_url`] routes all searches to the server.
+//!
+//! The response is chosen by keywords in the search query:
+//!
+//! | Query contains | Response |
+//! |----------------|----------|
+//! | `status-403` | 403 Forbidden |
+//! | `status-429` | 429 Too Many Requests with `Retry-After: 1` |
+//! | `status-500` | 500 Internal Server Error |
+//! | `captcha` | 200 with the engine's CAPTCHA/block page |
+//! | `consent` | Google: 302 to `/google/consent`, Bing: 200 with the cookie banner page, unless the consent cookie is sent |
+//! | `sorry` | 302 to `/google/sorry/index`, answered with 429 and Google's CAPTCHA page |
+//! | `timeout` | No response for 60 seconds |
+//! | `empty` | 200 with a page without results |
+//! | `flaky-N` | 503 for the first N requests, then results |
+//! | anything else | 200 with ten results for the query and page |
+//!
+//! Google result pages also set an `NID` session cookie, like the real one.
+//!
+//! The autocomplete endpoints (`/complete/search`, `/osjson.aspx`, `/ac/`)
+//! answer with three suggestions extending the query; their requests are
+//! counted by [`MockServer::hits`] as `suggest:<query>`.
+//!
+//! The server also acts as a plain HTTP forward proxy: requests in absolute
+//! form (`GET http://host/google/search?... HTTP/1.1`) are answered the same
+//! way and listed by [`MockServer::proxied`].
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const GOOGLE_CAPTCHA: &str = include_str!("../../tests/fixtures/google/captcha.html");
+const BING_CAPTCHA: &str = include_str!("../../tests/fixtures/bing/captcha.html");
+const DUCKDUCKGO_CAPTCHA: &str = include_str!("../../tests/fixtures/duckduckgo/captcha.html");
+const GOOGLE_CONSENT: &str = include_str!("../../tests/fixtures/google/consent.html");
+const BING_CONSENT: &str = include_str!("../../tests/fixtures/bing/consent.html");
+
+/// Mock search server running on a background thread
+///
+/// The server has its own runtime, so it outlives the runtimes of individual
+/// `#[tokio::test]` functions and can be shared through a static.
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<State>,
+}
+
+/// Request statistics shared with the server thread
+#[derive(Debug, Default)]
+struct State {
+    hits: Mutex<HashMap<String, usize>>,
+    connections: AtomicUsize,
+    proxied: Mutex<Vec<String>>,
+    proxy_authorization: Mutex<Option<String>>,
+}
+
+/// Response produced by the mock server
+struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    fn html(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: None,
+        }
+    }
+}
+
+impl MockServer {
+    /// Start the server on a random local port
+    pub fn start() -> Self {
+        let listener =
+            std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        listener
+            .set_nonblocking(true)
+            .expect("Failed to configure mock server socket");
+        let addr = listener
+            .local_addr()
+            .expect("Failed to get mock server address");
+
+        let server = Self {
+            addr,
+            state: Arc::new(State::default()),
+        };
+
+        let state = server.state.clone();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build mock server runtime");
+            runtime.block_on(async move {
+                let listener = TcpListener::from_std(listener).expect("Invalid mock listener");
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_connection(stream, state.clone()));
+                }
+            });
+        });
+
+        server
+    }
+
+    /// Address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL emulating an engine (google, bing, duckduckgo)
+    pub fn base_url(&self, engine: &str) -> String {
+        format!("http://{}/{}", self.addr, engine)
+    }
+
+    /// `[engine.base_urls]` config section routing every engine to this server
+    pub fn config_toml(&self) -> String {
+        format!(
+            "[engine.base_urls]\ngoogle = \"{}\"\nbing = \"{}\"\nduckduckgo = \"{}\"\n\
+             duckduckgo_suggest = \"{}\"\n",
+            self.base_url("google"),
+            self.base_url("bing"),
+            self.base_url("duckduckgo"),
+            self.base_url("duckduckgo")
+        )
+    }
+
+    /// Number of requests received for a query, across engines and pages
+    pub fn hits(&self, query: &str) -> usize {
+        self.state
+            .hits
+            .lock()
+            .unwrap()
+            .get(query)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// URLs requested through the server as a forward proxy
+    pub fn proxied(&self) -> Vec<String> {
+        self.state.proxied.lock().unwrap().clone()
+    }
+
+    /// `Proxy-Authorization` header of the last proxied request
+    pub fn proxy_authorization(&self) -> Option<String> {
+        self.state.proxy_authorization.lock().unwrap().clone()
+    }
+
+    /// Number of TCP connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<State>) {
+    state.connections.fetch_add(1, Ordering::SeqCst);
+
+    // Serve requests until the client closes the kept-alive connection; the
+    // engines only send GET requests without a body
+    let mut pending = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let head_end = loop {
+            if let Some(pos) = pending.windows(4).position(|window| window == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if pending.len() > 64 * 1024 {
+                return;
+            }
+            match stream.read(&mut buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => pending.extend_from_slice(&buffer[..n]),
+            }
+        };
+        let request: Vec<u8> = pending.drain(..head_end).collect();
+        let request = String::from_utf8_lossy(&request);
+        let target = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/");
+        let cookies = header(&request, "cookie").unwrap_or_default();
+
+        let url = if target.starts_with("http://") {
+            state.proxied.lock().unwrap().push(target.to_string());
+            *state.proxy_authorization.lock().unwrap() = header(&request, "proxy-authorization");
+            Url::parse(target)
+        } else {
+            Url::parse(&format!("http://mock{}", target))
+        };
+
+        let response = match url {
+            Ok(url) => route(&url, &cookies, &state.hits),
+            Err(_) => MockResponse::html(400, "Bad request"),
+        };
+
+        if let Some(delay) = response.delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n",
+            response.status,
+            reqwest::StatusCode::from_u16(response.status)
+                .ok()
+                .and_then(|status| status.canonical_reason())
+                .unwrap_or("Unknown"),
+            response.body.len()
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+
+        if stream.write_all(head.as_bytes()).await.is_err()
+            || stream.write_all(response.body.as_bytes()).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Get a request header by (case-insensitive) name
+fn header(request: &str, name: &str) -> Option<String> {
+    request
+        .lines()
+        .find_map(|line| {
+            line.split_once(':')
+                .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+        })
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Choose the response for a request
+fn route(url: &Url, cookies: &str, hits: &Mutex<HashMap<String, usize>>) -> MockResponse {
+    let engine = url
+        .path_segments()
+        .and_then(|mut segments| segments.next())
+        .unwrap_or_default()
+        .to_string();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    if url.path().starts_with("/google/sorry/") {
+        return MockResponse::html(429, GOOGLE_CAPTCHA);
+    }
+    if url.path().starts_with("/google/consent") {
+        return MockResponse::html(200, GOOGLE_CONSENT);
+    }
+    if ["/complete/search", "/osjson.aspx", "/ac/"]
+        .iter()
+        .any(|endpoint| url.path().ends_with(endpoint))
+    {
+        let query = param("q").or_else(|| param("query")).unwrap_or_default();
+        *hits
+            .lock()
+            .unwrap()
+            .entry(format!("suggest:{}", query))
+            .or_insert(0) += 1;
+        return MockResponse::html(200, suggestions(&engine, &query));
+    }
+    let Some(query) = param("q") else {
+        return MockResponse::html(404, "Not found");
+    };
+    let start = ["start", "first", "s"]
+        .iter()
+        .find_map(|name| param(name))
+        .and_then(|start| start.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let hit = {
+        let mut hits = hits.lock().unwrap();
+        let count = hits.entry(query.clone()).or_insert(0);
+        *count += 1;
+        *count
+    };
+
+    if query.contains("status-403") {
+        return MockResponse::html(403, "Forbidden");
+    }
+    if query.contains("status-429") {
+        let mut response = MockResponse::html(429, "Too Many Requests");
+        response.headers.push(("Retry-After", "1".to_string()));
+        return response;
+    }
+    if query.contains("sorry") {
+        let mut response = MockResponse::html(302, "");
+        response.headers.push((
+            "Location",
+            format!(
+                "/{}/sorry/index?continue={}",
+                engine,
+                urlencoding::encode(url.as_str())
+            ),
+        ));
+        return response;
+    }
+    if query.contains("consent") {
+        match engine.as_str() {
+            "google" if !cookies.contains("SOCS=") => {
+                let mut response = MockResponse::html(302, "");
+                response.headers.push((
+                    "Location",
+                    format!(
+                        "/google/consent?continue={}",
+                        urlencoding::encode(url.as_str())
+                    ),
+                ));
+                return response;
+            }
+            "bing" if !cookies.contains("BCP=") => return MockResponse::html(200, BING_CONSENT),
+            _ => {}
+        }
+    }
+    if query.contains("status-500") {
+        return MockResponse::html(500, "Internal Server Error");
+    }
+    if query.contains("timeout") {
+        let mut response = MockResponse::html(200, "");
+        response.delay = Some(Duration::from_secs(60));
+        return response;
+    }
+    if let Some(failures) = flaky_failures(&query) {
+        if hit <= failures {
+            return MockResponse::html(503, "Service Unavailable");
+        }
+    }
+
+    let page = match engine.as_str() {
+        "google" if query.contains("captcha") => GOOGLE_CAPTCHA.to_string(),
+        "bing" if query.contains("captcha") => BING_CAPTCHA.to_string(),
+        "duckduckgo" if query.contains("captcha") => DUCKDUCKGO_CAPTCHA.to_string(),
+        "google" | "bing" | "duckduckgo" => {
+            let count = if query.contains("empty") { 0 } else { 10 };
+            results_page(&engine, &query, start, count)
+        }
+        _ => return MockResponse::html(404, "Not found"),
+    };
+
+    let mut response = MockResponse::html(200, page);
+    if engine == "google" {
+        response.headers.push((
+            "Set-Cookie",
+            "NID=mock-session; Path=/; HttpOnly".to_string(),
+        ));
+    }
+    response
+}
+
+/// Autocomplete response in the OpenSearch suggestions format
+fn suggestions(engine: &str, query: &str) -> String {
+    let suggestions = [
+        format!("{} tutorial", query),
+        format!("{} book", query),
+        format!("{} {}", query, engine),
+    ];
+    serde_json::json!([query, suggestions]).to_string()
+}
+
+/// Number of failures requested by a `flaky-N` keyword
+fn flaky_failures(query: &str) -> Option<usize> {
+    query
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix("flaky-"))
+        .and_then(|count| count.parse().ok())
+}
+
+/// Generate a results page in the markup of the given engine
+fn results_page(engine: &str, query: &str, start: usize, count: usize) -> String {
+    let query = escape(query);
+    let slug = query.replace(' ', "-");
+
+    let results: String = (start + 1..=start + count)
+        .map(|n| {
+            let title = format!("{} result {}", query, n);
+            let url = format!("https://example.com/{}/{}", slug, n);
+            let description = format!("Description of {} result {}.", query, n);
+            match engine {
+                "google" => format!(
+                    r#"<div class="MjjYud"><a jsname="UWckNb" href="{url}"><h3 class="LC20lb">{title}</h3></a><div class="VwiC3b">{description}</div></div>"#
+                ),
+                "bing" => format!(
+                    r#"<li class="b_algo"><h2><a href="{url}">{title}</a></h2><div class="b_caption"><p>{description}</p></div></li>"#
+                ),
+                _ => format!(
+                    r#"<div class="result"><h2 class="result__title"><a class="result__a" href="{url}">{title}</a></h2><a class="result__snippet" href="{url}">{description}</a></div>"#
+                ),
+            }
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{} - Mock {}</title></head><body>{}</body></html>",
+        query, engine, results
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
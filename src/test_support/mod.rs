@@ -0,0 +1,8 @@
+//! Test support utilities, available with the `test-support` feature
+//!
+//! These are meant for integration tests and demos and are not part of the
+//! stable API.
+
+pub mod mock_server;
+
+pub use mock_server::MockServer;
@@ -1,12 +1,20 @@
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
-use std::io;
 
-use crate::{search::models::POLL_TIMEOUT, App};
+use crate::{
+    core::{
+        app::{Command, HistorySearch, SuggestionSource},
+        cli::format_age,
+        editor,
+        keymap::{Action, KeyChord, Mode},
+    },
+    search::models::POLL_TIMEOUT,
+    App,
+};
 
 /// Render the user interface
 pub fn ui(frame: &mut Frame, app: &mut App) {
@@ -22,34 +30,52 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         ])
         .split(frame.area());
 
+    // Scroll the input sideways so the cursor stays inside the box
+    let loading = match app.keymap.key(Mode::Global, Action::Cancel) {
+        Some(key) => format!(" [Searching... {} to cancel]", key),
+        None => " [Searching...]".to_string(),
+    };
+    let input_width = chunks[0].width.saturating_sub(2) as usize;
+    let (visible, cursor_column) = if app.is_loading {
+        editor::visible_window(
+            &app.input,
+            app.cursor,
+            input_width.saturating_sub(loading.len()),
+        )
+    } else {
+        editor::visible_window(&app.input, app.cursor, input_width)
+    };
+
     let input_content = if app.is_loading {
         // If searching, show a loading indicator
         Line::from(vec![
-            Span::raw(&app.input), // Show current input
+            Span::raw(visible), // Show current input
             Span::styled(
-                " [Searching...]",                  // Add a loading indicator
+                loading,                            // Add a loading indicator
                 Style::default().fg(Color::Yellow), // And highlight it
             ),
         ])
     } else {
-        // If not searching, show a vertical line
-        Line::from(vec![
-            Span::raw(&app.input), // Show current input
-            Span::styled(
-                "|",                                // Add a vertical line
-                Style::default().fg(Color::Yellow), // And highlight it
-            ),
-        ])
+        Line::from(Span::raw(visible))
     };
 
     let input = Paragraph::new(input_content).block(Block::default().borders(Borders::ALL).title(
         if app.input_mode {
-            "Input (Press Esc to stop editing)"
+            format!(
+                "Input{}",
+                press(app, Mode::Input, Action::StopEditing, "stop editing")
+            )
         } else {
-            "Input (Press 'i' to edit)"
+            format!(
+                "Input{}",
+                press(app, Mode::Results, Action::EditInput, "edit")
+            )
         },
     ));
     frame.render_widget(input, chunks[0]);
+    if app.input_mode {
+        frame.set_cursor_position((chunks[0].x + 1 + cursor_column as u16, chunks[0].y + 1));
+    }
 
     // Search stats
     let stats_text = if !app.search_results.is_empty() {
@@ -62,7 +88,7 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
 
     let stats = Paragraph::new(Line::from(vec![
         Span::styled(
-            "●",
+            if app.is_loading { app.spinner() } else { "●" },
             Style::default().fg(if app.is_loading {
                 Color::Yellow
             } else if !app.search_results.is_empty() {
@@ -75,11 +101,24 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         Span::raw(" | "),
         Span::styled(
             format!(
-                "Engine: {} (Press 'e' to change)",
-                app.search_engine.as_str()
+                "Engine: {}{}",
+                if app.all_engines {
+                    "All"
+                } else {
+                    app.search_engine.as_str()
+                },
+                press(app, Mode::Results, Action::NextEngine, "change")
             ),
             Style::default().fg(Color::Cyan),
         ),
+        Span::styled(
+            if app.offline { " | Offline" } else { "" },
+            Style::default().fg(Color::Magenta),
+        ),
+        Span::styled(
+            if app.incognito { " | Incognito" } else { "" },
+            Style::default().fg(Color::Magenta),
+        ),
     ]));
     frame.render_widget(stats, chunks[1]);
 
@@ -110,9 +149,21 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
 
     let results_list = List::new(results)
         .block(Block::default().borders(Borders::ALL).title(format!(
-            "(Page {}/{})",
+            "(Page {}/{}){}{}",
             app.page + 1,
-            app.total_pages
+            app.total_pages,
+            if app.stale {
+                " [stale cache]"
+            } else if app.cached {
+                " [cached]"
+            } else {
+                ""
+            },
+            if app.refresh.is_some() {
+                " [refreshing...]"
+            } else {
+                ""
+            }
         )))
         .highlight_style(
             Style::default()
@@ -133,101 +184,226 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
             .block(Block::default().borders(Borders::ALL).title("Error"));
         frame.render_widget(error_message, chunks[3]);
     } else {
-        let help = if app.input_mode {
-            "Ctrl+U: Clear Input | Press Esc to exit input mode | Enter to search"
+        let help = app.keymap.hint(if app.input_mode {
+            Mode::Input
         } else {
-            "j/k: Navigate | h/l: Change Page | r: Clear Results | Enter: Open URL | i: Input | q: Quit | e: Change Engine"
-        };
+            Mode::Results
+        });
         let status = Paragraph::new(help)
             .style(Style::default())
             .block(Block::default().borders(Borders::ALL).title("Help"));
         frame.render_widget(status, chunks[3]);
     }
+
+    if let Some(search) = &app.history_search {
+        history_popup(frame, app, search, chunks[2]);
+    } else if app.input_mode && !app.suggestions.is_empty() {
+        suggestion_dropdown(frame, app, chunks[0]);
+    }
+
+    if app.help.is_some() {
+        help_overlay(frame, app);
+    }
+}
+
+/// Render the key bindings of every mode in a scrollable box over the
+/// whole screen
+fn help_overlay(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(80);
+    let height = area.height.saturating_sub(2);
+    let area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let sections = app.keymap.help();
+    // Keys longer than the column get a line of their own
+    const KEY_COLUMN: usize = 20;
+    let key_width = sections
+        .iter()
+        .flat_map(|(_, entries)| entries.iter().map(|(keys, _)| keys.chars().count()))
+        .filter(|&width| width <= KEY_COLUMN)
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (mode, entries) in sections {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::styled(
+            mode.title(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        for (keys, description) in entries {
+            let key_style = Style::default().fg(Color::Cyan);
+            if keys.chars().count() > key_width {
+                lines.push(Line::styled(format!("  {}", keys), key_style));
+                lines.push(Line::from(format!("  {:key_width$}  {}", "", description)));
+            } else {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:<key_width$}  ", keys), key_style),
+                    Span::raw(description),
+                ]));
+            }
+        }
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Keys ({})", app.keymap.hint(Mode::Help)));
+    let page = block.inner(area).height as usize;
+    let total = lines.len();
+    let Some(help) = app.help.as_mut() else {
+        return;
+    };
+    help.page = page;
+    help.scroll = help.scroll.min(total.saturating_sub(page));
+    let block = if total > page {
+        block.title_bottom(
+            Line::from(format!(
+                " {}-{} of {} ",
+                help.scroll + 1,
+                (help.scroll + page).min(total),
+                total
+            ))
+            .right_aligned(),
+        )
+    } else {
+        block
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((help.scroll as u16, 0)),
+        area,
+    );
+}
+
+/// Title hint like ` (Press 'i' to edit)` naming the key bound to `action`,
+/// or nothing when the action is unbound
+fn press(app: &App, mode: Mode, action: Action, what: &str) -> String {
+    match app.keymap.key(mode, action) {
+        Some(KeyChord {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+        }) if c != ' ' => format!(" (Press '{}' to {})", c, what),
+        Some(key) => format!(" (Press {} to {})", key, what),
+        None => String::new(),
+    }
+}
+
+/// Render the suggestions in a dropdown right under the input box
+fn suggestion_dropdown(frame: &mut Frame, app: &App, input_area: Rect) {
+    let top = input_area.bottom();
+    let height = (app.suggestions.len() as u16 + 2).min(frame.area().bottom().saturating_sub(top));
+    let area = Rect::new(input_area.x, top, input_area.width, height);
+
+    let items: Vec<ListItem> = app
+        .suggestions
+        .iter()
+        .map(|suggestion| {
+            let mut line = vec![Span::raw(suggestion.text.as_str())];
+            if suggestion.source == SuggestionSource::History {
+                line.push(Span::styled(
+                    "  history",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(line))
+        })
+        .collect();
+    let mut state = ListState::default().with_selected(app.selected_suggestion);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Suggestions ({})",
+            app.keymap.hint(Mode::Suggestions)
+        )))
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Render the Ctrl-R history search over the results area
+fn history_popup(frame: &mut Frame, app: &App, search: &HistorySearch, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        "History ({})",
+        app.keymap.hint(Mode::HistorySearch)
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [pattern_area, list_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+    let (visible, cursor_column) = editor::visible_window(
+        &search.pattern,
+        search.cursor,
+        pattern_area.width.saturating_sub(2) as usize,
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Yellow)),
+            Span::raw(visible),
+        ])),
+        pattern_area,
+    );
+    frame.set_cursor_position((pattern_area.x + 2 + cursor_column as u16, pattern_area.y));
+
+    let matches: Vec<ListItem> = app
+        .history_matches()
+        .into_iter()
+        .map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::raw(entry.query.as_str()),
+                Span::styled(
+                    format!("  {} · {} ago", entry.engine, format_age(entry.age())),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+    let mut state = ListState::default().with_selected(Some(search.selected));
+    let list = List::new(matches).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, list_area, &mut state);
 }
 
 /// Run the application
-pub async fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    app: &mut App,
-) -> Result<()> {
+pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
     loop {
         terminal.draw(|f| ui(f, app))?;
 
         if event::poll(POLL_TIMEOUT)? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') if !app.input_mode => break,
-                        KeyCode::Char('i') if !app.input_mode => {
-                            app.input_mode = true;
-                        }
-                        KeyCode::Esc if app.input_mode => {
-                            app.input_mode = false;
-                        }
-                        KeyCode::Char('h')
-                            if app.input_mode
-                                && key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                        {
-                            app.input.pop();
-                        }
-                        KeyCode::Char('u')
-                            if app.input_mode
-                                && key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                        {
-                            app.clear_input();
-                        }
-                        KeyCode::Char('h')
-                            if app.input_mode
-                                && key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                        {
-                            app.input.pop();
-                        }
-                        KeyCode::Char('r') if !app.input_mode => {
-                            app.clear_results();
-                        }
-                        KeyCode::Char('e') if !app.input_mode => {
-                            app.search_engine = app.search_engine.next();
-                            if !app.input.is_empty() {
-                                app.perform_search().await?;
-                            }
-                        }
-                        KeyCode::Char(c) if app.input_mode => {
-                            app.input.push(c);
-                        }
-                        KeyCode::Backspace if app.input_mode => {
-                            app.input.pop();
-                        }
-                        KeyCode::Enter if app.input_mode => {
-                            app.perform_search().await?;
-                        }
-                        KeyCode::Enter if !app.input_mode => {
-                            app.open_selected_url()?;
-                        }
-                        KeyCode::Char('j') | KeyCode::Down if !app.input_mode => {
-                            let i = match app.list_state.selected() {
-                                Some(i) => i.saturating_add(1),
-                                None => 0,
-                            };
-                            app.list_state.select(Some(i));
-                        }
-                        KeyCode::Char('k') | KeyCode::Up if !app.input_mode => {
-                            let i = match app.list_state.selected() {
-                                Some(i) => i.saturating_sub(1),
-                                None => 0,
-                            };
-                            app.list_state.select(Some(i));
-                        }
-                        KeyCode::Char('h') | KeyCode::Left if !app.input_mode => {
-                            app.change_page(-1).await?;
-                        }
-                        KeyCode::Char('l') | KeyCode::Right if !app.input_mode => {
-                            app.change_page(1).await?;
-                        }
-                        _ => {}
+                    match app.handle_key(key) {
+                        Command::None => {}
+                        Command::Quit => break,
+                        Command::Search => app.start_search(app.cache_mode()),
+                        Command::ChangePage(direction) => app.change_page(direction),
+                        Command::OpenUrl => app.open_selected_url()?,
+                        Command::Refresh => app.start_refresh(),
                     }
                 }
             }
         }
+
+        // Searches run in the background and report back between key presses
+        app.poll_updates();
     }
 
     Ok(())
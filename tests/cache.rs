@@ -0,0 +1,273 @@
+//! Results cache keys, entry sizes and the on-disk cache tier
+
+mod common;
+
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use common::SERVER;
+use search_in_terminal::{
+    search::{
+        cache::{
+            CacheFilter, CacheKey, CacheStats, CachedPage, DiskCache, Freshness, ResponseCache,
+        },
+        engine::{SearchEngine, RESPONSE_CACHE},
+    },
+    SearchResult, CONFIG,
+};
+
+fn cache_dir(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("cache")
+        .join(name)
+}
+
+/// A disk cache in a fresh directory
+fn disk_cache(name: &str, max_bytes: u64, time_to_live: Duration) -> DiskCache {
+    let dir = cache_dir(name);
+    let _ = fs::remove_dir_all(&dir);
+    DiskCache::new(dir, max_bytes, time_to_live)
+}
+
+/// A page of `count` results with `text_len` bytes of text each
+fn page(count: usize, text_len: usize) -> CachedPage {
+    CachedPage::new(
+        (0..count)
+            .map(|i| SearchResult {
+                title: format!("Result {}", i),
+                url: format!("https://example.com/{}", i),
+                description: "x".repeat(text_len),
+            })
+            .collect(),
+        None,
+    )
+}
+
+fn setup() {
+    let _ = fs::remove_dir_all(cache_dir("session"));
+    common::mock_config(
+        "cache",
+        &format!(
+            "[cache]\ndisk = true\ndisk_dir = {:?}\n",
+            cache_dir("session").display().to_string()
+        ),
+    );
+}
+
+#[test]
+fn keys_are_normalized() {
+    assert_eq!(
+        CacheKey::new("Google", "  Rust   Programming ", 10, "en-US"),
+        CacheKey::new("google", "rust programming", 10, "en-us")
+    );
+    assert_ne!(
+        CacheKey::new("google", "rust", 0, "en-US"),
+        CacheKey::new("google", "rust", 10, "en-US")
+    );
+    assert_ne!(
+        CacheKey::new("google", "rust", 0, "en-US"),
+        CacheKey::new("bing", "rust", 0, "en-US")
+    );
+    assert_ne!(
+        CacheKey::new("google", "rust", 0, "en-US"),
+        CacheKey::new("google", "rust", 0, "de-DE")
+    );
+}
+
+#[test]
+fn disk_entries_are_returned_until_they_expire() {
+    let key = CacheKey::new("google", "rust", 0, "en-US");
+    let page = page(3, 20);
+
+    let cache = disk_cache("fresh", 1024 * 1024, Duration::from_secs(60));
+    assert_eq!(cache.get(&key), None);
+    cache.insert(&key, &page).unwrap();
+    assert_eq!(cache.get(&key), Some(page.clone()));
+    assert_eq!(
+        cache.get(&CacheKey::new("google", "rust", 10, "en-US")),
+        None
+    );
+
+    let expired = disk_cache("expired", 1024 * 1024, Duration::ZERO);
+    expired.insert(&key, &page).unwrap();
+    assert_eq!(expired.get(&key), None);
+}
+
+#[test]
+fn oldest_disk_entries_are_evicted_beyond_the_size_limit() {
+    let keys: Vec<_> = (0..3)
+        .map(|page| CacheKey::new("bing", "rust", page * 10, "en-US"))
+        .collect();
+    let page = page(1, 1000);
+
+    // Room for two entries of a little over a kilobyte each
+    let cache = disk_cache("evict", 2500, Duration::from_secs(60));
+    for key in &keys {
+        cache.insert(key, &page).unwrap();
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    assert_eq!(cache.get(&keys[0]), None);
+    assert!(cache.get(&keys[1]).is_some());
+    assert!(cache.get(&keys[2]).is_some());
+    assert!(cache.size().unwrap() <= 2500);
+}
+
+#[test]
+fn rewriting_an_entry_does_not_evict_others() {
+    let keys: Vec<_> = (0..2)
+        .map(|page| CacheKey::new("bing", "rust", page * 10, "en-US"))
+        .collect();
+    let page = page(1, 1000);
+
+    let cache = disk_cache("rewrite", 2500, Duration::from_secs(60));
+    cache.insert(&keys[0], &page).unwrap();
+    for _ in 0..5 {
+        cache.insert(&keys[1], &page).unwrap();
+    }
+
+    assert!(cache.get(&keys[0]).is_some());
+    assert!(cache.get(&keys[1]).is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn disk_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let cache = disk_cache("private", 1024 * 1024, Duration::from_secs(60));
+    let key = CacheKey::new("google", "private query", 0, "en-US");
+    cache.insert(&key, &page(1, 10)).unwrap();
+    cache.add_stats(CacheStats { hits: 1, misses: 0 }).unwrap();
+
+    let dir = cache_dir("private");
+    for path in [cache.path(&key), dir.join("stats")] {
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+    }
+    assert_eq!(fs::read_dir(dir).unwrap().count(), 2);
+}
+
+#[test]
+fn entries_are_listed_and_purged_by_filter() {
+    let cache = disk_cache("purge", 1024 * 1024, Duration::from_secs(60));
+    let mut old = page(2, 10);
+    old.fetched_at -= 2 * 60 * 60;
+    cache
+        .insert(&CacheKey::new("google", "rust lang", 0, "en-US"), &old)
+        .unwrap();
+    cache
+        .insert(
+            &CacheKey::new("google", "go lang", 0, "en-US"),
+            &page(3, 10),
+        )
+        .unwrap();
+    cache
+        .insert(
+            &CacheKey::new("bing", "rust book", 10, "en-US"),
+            &page(1, 10),
+        )
+        .unwrap();
+
+    let all = cache.list(&CacheFilter::default()).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all.last().unwrap().key.query, "rust lang");
+    assert_eq!(all.last().unwrap().results, 2);
+
+    let google = CacheFilter {
+        engine: Some("Google".to_string()),
+        ..CacheFilter::default()
+    };
+    assert_eq!(cache.list(&google).unwrap().len(), 2);
+    assert!(CacheFilter::default().is_empty());
+    assert!(!google.is_empty());
+    let rust = CacheFilter {
+        query: Some("RUST".to_string()),
+        ..CacheFilter::default()
+    };
+    assert_eq!(cache.list(&rust).unwrap().len(), 2);
+
+    let older = CacheFilter {
+        older_than: Some(Duration::from_secs(60 * 60)),
+        ..CacheFilter::default()
+    };
+    assert_eq!(cache.purge(&older).unwrap(), 1);
+    assert_eq!(cache.purge(&google).unwrap(), 1);
+    let left = cache.list(&CacheFilter::default()).unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].key.engine, "bing");
+}
+
+#[test]
+fn hits_and_misses_are_totalled_on_disk() {
+    let cache = disk_cache("stats", 1024 * 1024, Duration::from_secs(60));
+    assert_eq!(cache.stats(), CacheStats::default());
+    assert_eq!(cache.stats().hit_rate(), None);
+
+    cache.add_stats(CacheStats { hits: 2, misses: 0 }).unwrap();
+    cache.add_stats(CacheStats { hits: 1, misses: 1 }).unwrap();
+    assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 1 });
+    assert_eq!(cache.stats().hit_rate(), Some(0.75));
+
+    // The totals are not an entry
+    assert!(cache.list(&CacheFilter::default()).unwrap().is_empty());
+}
+
+#[test]
+fn freshness_follows_the_age_of_a_page() {
+    assert_eq!(Freshness::of(0, 100, 50), Freshness::Fresh);
+    assert_eq!(Freshness::of(79, 100, 50), Freshness::Fresh);
+    assert_eq!(Freshness::of(80, 100, 50), Freshness::Expiring);
+    assert_eq!(Freshness::of(100, 100, 50), Freshness::Stale);
+    assert_eq!(Freshness::of(149, 100, 50), Freshness::Stale);
+    assert_eq!(Freshness::of(150, 100, 50), Freshness::Expired);
+    // Without a stale window, pages expire with their time-to-live
+    assert_eq!(Freshness::of(100, 100, 0), Freshness::Expired);
+}
+
+#[test]
+fn page_size_grows_with_its_content() {
+    let small = page(1, 10);
+    let large = page(10, 1000);
+    assert!(small.size() > 10);
+    assert!(large.size() > 10 * 1000);
+
+    let with_html = CachedPage {
+        html: Some("<html></html>".repeat(100)),
+        ..small.clone()
+    };
+    assert_eq!(with_html.size(), small.size() + 1300);
+}
+
+#[tokio::test]
+async fn responses_are_reused_by_the_next_session() {
+    setup();
+    let results = SearchEngine::favor("duckduckgo")
+        .search("disk cache", 0)
+        .await
+        .unwrap();
+    assert_eq!(SERVER.hits("disk cache"), 1);
+
+    // Equivalent query in the same session comes from memory
+    SearchEngine::favor("duckduckgo")
+        .search("Disk  Cache", 0)
+        .await
+        .unwrap();
+    assert_eq!(SERVER.hits("disk cache"), 1);
+
+    // A new session only has the disk tier, holding parsed results
+    let next_session = ResponseCache::from_config(&CONFIG.cache);
+    let key = CacheKey::new("DuckDuckGo", "disk cache", 0, &CONFIG.search.locale);
+    let page = next_session.get(&key).await.unwrap();
+    assert_eq!(page.results, results);
+    assert_eq!(page.html, None);
+
+    // One miss and one hit, counted for this session and added to the
+    // totals on disk when it ends
+    let stats = CacheStats { hits: 1, misses: 1 };
+    assert_eq!(RESPONSE_CACHE.stats(), stats);
+    let disk = DiskCache::from_config(&CONFIG.cache).unwrap();
+    assert_eq!(disk.stats(), CacheStats::default());
+    RESPONSE_CACHE.save_stats().await;
+    RESPONSE_CACHE.save_stats().await;
+    assert_eq!(disk.stats(), stats);
+}
@@ -0,0 +1,131 @@
+//! Command line parsing
+
+use std::time::Duration;
+
+use search_in_terminal::{
+    core::cli::{format_age, format_bytes, parse_duration, CliCommand, USAGE},
+    search::cache::CacheFilter,
+};
+
+#[test]
+fn without_subcommand_the_tui_starts() {
+    assert_eq!(
+        CliCommand::parse(Vec::<String>::new()).unwrap(),
+        CliCommand::Tui {
+            offline: false,
+            incognito: false,
+            query: None
+        }
+    );
+    assert_eq!(
+        CliCommand::parse(["--offline"]).unwrap(),
+        CliCommand::Tui {
+            offline: true,
+            incognito: false,
+            query: None
+        }
+    );
+    assert_eq!(
+        CliCommand::parse(["--incognito", "--offline"]).unwrap(),
+        CliCommand::Tui {
+            offline: true,
+            incognito: true,
+            query: None
+        }
+    );
+}
+
+#[test]
+fn a_query_prefills_the_input() {
+    assert_eq!(
+        CliCommand::parse(["rust programming"]).unwrap(),
+        CliCommand::Tui {
+            offline: false,
+            incognito: false,
+            query: Some("rust programming".to_string())
+        }
+    );
+    assert_eq!(
+        CliCommand::parse(["--offline", "search", "--incognito"]).unwrap(),
+        CliCommand::Tui {
+            offline: true,
+            incognito: true,
+            query: Some("search".to_string())
+        }
+    );
+}
+
+#[test]
+fn unknown_arguments_are_rejected() {
+    for args in [&["--offlin"][..], &["-e", "google", "rust"], &["-o"]] {
+        let error = CliCommand::parse(args).unwrap_err().to_string();
+        assert!(error.starts_with("Unknown option -"), "{}", error);
+        assert!(error.ends_with(USAGE), "{}", error);
+    }
+
+    // A query with spaces has to be quoted
+    let error = CliCommand::parse(["rust", "lang"]).unwrap_err().to_string();
+    assert!(error.starts_with("Unexpected argument lang"), "{}", error);
+    assert!(error.ends_with(USAGE), "{}", error);
+}
+
+#[test]
+fn cookies_clear_is_parsed() {
+    assert_eq!(
+        CliCommand::parse(["cookies", "clear"]).unwrap(),
+        CliCommand::ClearCookies
+    );
+    assert!(CliCommand::parse(["cookies", "list"]).is_err());
+}
+
+#[test]
+fn cache_commands_take_filters() {
+    assert_eq!(
+        CliCommand::parse(["cache", "stats"]).unwrap(),
+        CliCommand::CacheStats(CacheFilter::default())
+    );
+    assert_eq!(
+        CliCommand::parse(["cache", "list", "--engine", "Bing"]).unwrap(),
+        CliCommand::CacheList(CacheFilter {
+            engine: Some("Bing".to_string()),
+            ..CacheFilter::default()
+        })
+    );
+    assert_eq!(
+        CliCommand::parse(["cache", "purge", "--query", "rust", "--older-than", "2h"]).unwrap(),
+        CliCommand::CachePurge(CacheFilter {
+            query: Some("rust".to_string()),
+            older_than: Some(Duration::from_secs(2 * 60 * 60)),
+            ..CacheFilter::default()
+        })
+    );
+
+    assert!(CliCommand::parse(["cache"]).is_err());
+    assert!(CliCommand::parse(["cache", "show"]).is_err());
+    assert!(CliCommand::parse(["cache", "list", "--engine"]).is_err());
+    assert!(CliCommand::parse(["cache", "list", "--since", "1d"]).is_err());
+}
+
+#[test]
+fn durations_take_an_optional_unit() {
+    assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration("15m").unwrap(), Duration::from_secs(15 * 60));
+    assert_eq!(
+        parse_duration("7d").unwrap(),
+        Duration::from_secs(7 * 24 * 60 * 60)
+    );
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("h").is_err());
+    assert!(parse_duration("3w").is_err());
+}
+
+#[test]
+fn sizes_and_ages_are_formatted_in_their_largest_unit() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(50 * 1024 * 1024), "50.0 MiB");
+    assert_eq!(format_age(59), "59s");
+    assert_eq!(format_age(60 * 60 + 1), "1h");
+    assert_eq!(format_age(3 * 24 * 60 * 60), "3d");
+}
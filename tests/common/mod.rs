@@ -0,0 +1,102 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::{fs, path::Path, sync::Once};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use once_cell::sync::Lazy;
+use ratatui::{backend::TestBackend, Terminal};
+use search_in_terminal::{test_support::MockServer, ui::terminal::ui, App};
+
+static SETUP: Once = Once::new();
+
+/// Mock search server the engines of [`mock_config`] point at
+pub static SERVER: Lazy<MockServer> = Lazy::new(MockServer::start);
+
+/// Settings every test against the mock server starts from: one attempt,
+/// no throttling and no cookies kept on disk
+const MOCK_BASE: &str = "[search]\nmax_retries = 1\nmax_jitter = 1\n\n\
+                         [rate_limit]\nrequests_per_minute = 6000\nburst = 100\n\n\
+                         [cookies]\npersist = false\n";
+
+/// Point the global configuration at a test config file
+///
+/// The configuration is loaded once per process, so every test in a binary
+/// must call this with the same `contents` before touching the crate.
+pub fn use_config(name: &str, contents: &str) {
+    SETUP.call_once(|| {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+        fs::create_dir_all(&dir).unwrap();
+
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, contents).unwrap();
+        std::env::set_var("ST_CONFIG", &config_path);
+    });
+}
+
+/// Point the global configuration at the mock server
+///
+/// `extra_toml` is merged over the shared settings and the server's base
+/// URLs, so it can both add sections and override single keys like
+/// `[search] max_retries`.
+pub fn mock_config(name: &str, extra_toml: &str) {
+    let mut config: toml::Table = toml::from_str(MOCK_BASE).unwrap();
+    merge(&mut config, toml::from_str(&SERVER.config_toml()).unwrap());
+    merge(&mut config, toml::from_str(extra_toml).unwrap());
+    use_config(name, &toml::to_string(&config).unwrap());
+}
+
+/// Merge `extra` into `base`, table by table
+fn merge(base: &mut toml::Table, extra: toml::Table) {
+    for (key, value) in extra {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(extra)) => merge(table, extra),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A key press without modifiers
+pub fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+/// A character typed with Ctrl
+pub fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+/// Path of a file or directory under `tests/fixtures`
+pub fn fixture_path(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+/// Render the app into a terminal of the given size
+pub fn draw(app: &mut App, width: u16, height: u16) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| ui(frame, app)).unwrap();
+    terminal
+}
+
+/// Render the app into plain text, one line per terminal row
+pub fn render(app: &mut App, width: u16, height: u16) -> String {
+    let terminal = draw(app, width, height);
+    let buffer = terminal.backend().buffer();
+
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
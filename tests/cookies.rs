@@ -0,0 +1,105 @@
+//! Cookie jars persisted between sessions and clearing them
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use search_in_terminal::{
+    core::config::CookieConfig,
+    search::{
+        engine::SearchEngine,
+        transport::{clear_cookies, cookie_path, load_cookies},
+    },
+    CONFIG,
+};
+
+fn cookie_dir(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("cookies")
+        .join(name)
+}
+
+fn setup() {
+    common::mock_config(
+        "cookies",
+        &format!(
+            "[cookies]\npersist = true\ndir = {:?}\n",
+            cookie_dir("jar").display().to_string()
+        ),
+    );
+}
+
+#[tokio::test]
+async fn session_and_consent_cookies_are_saved_per_engine() {
+    setup();
+    SearchEngine::favor("google")
+        .search("consent cookies", 0)
+        .await
+        .unwrap();
+
+    let path = cookie_path(&CONFIG.cookies, "Google");
+    assert!(path.ends_with("google.json"), "{}", path.display());
+
+    // The session cookie has no expiry but is kept for the next session
+    let store = load_cookies(&CONFIG.cookies, "Google");
+    let names: Vec<_> = store.iter_any().map(|cookie| cookie.name()).collect();
+    assert!(names.contains(&"NID"), "{:?}", names);
+    assert!(names.contains(&"SOCS"), "{:?}", names);
+    assert!(!cookie_path(&CONFIG.cookies, "Bing").exists());
+}
+
+#[test]
+fn clearing_removes_all_cookie_files() {
+    setup();
+    let config = CookieConfig {
+        persist: true,
+        dir: cookie_dir("clear"),
+    };
+    assert_eq!(clear_cookies(&config).unwrap(), 0);
+
+    fs::create_dir_all(&config.dir).unwrap();
+    fs::write(cookie_path(&config, "google"), "").unwrap();
+    fs::write(cookie_path(&config, "bing"), "").unwrap();
+    assert_eq!(clear_cookies(&config).unwrap(), 2);
+    assert!(!cookie_path(&config, "google").exists());
+    assert_eq!(load_cookies(&config, "google").iter_any().count(), 0);
+}
+
+#[tokio::test]
+async fn unchanged_jars_are_not_written() {
+    setup();
+    let path = cookie_path(&CONFIG.cookies, "DuckDuckGo");
+    let _ = fs::remove_file(&path);
+    // DuckDuckGo sets no cookies, so its empty jar never needs saving
+    SearchEngine::favor("duckduckgo")
+        .search("no cookies", 0)
+        .await
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn cookie_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    setup();
+    SearchEngine::favor("google")
+        .search("private cookies", 0)
+        .await
+        .unwrap();
+
+    let mode = fs::metadata(cookie_path(&CONFIG.cookies, "Google"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // Jars are written through a temporary file renamed into place
+    let leftovers: Vec<_> = fs::read_dir(&CONFIG.cookies.dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
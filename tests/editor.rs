@@ -0,0 +1,142 @@
+//! Cursor movement, editing and scrolling in the query input
+
+mod common;
+
+use common::{ctrl, key};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use search_in_terminal::core::{
+    editor::{clamp, handle_key, visible_window},
+    keymap::Keymap,
+};
+
+/// Text and cursor after pressing `keys` on an empty input
+fn edit(keys: &[KeyEvent]) -> (String, usize) {
+    let mut text = String::new();
+    let mut cursor = 0;
+    let keymap = Keymap::default();
+    for &key in keys {
+        handle_key(&mut text, &mut cursor, key, &keymap);
+    }
+    (text, cursor)
+}
+
+fn typed(text: &str) -> Vec<KeyEvent> {
+    text.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+fn alt(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+}
+
+#[test]
+fn characters_are_inserted_at_the_cursor() {
+    let mut keys = typed("rust lang");
+    keys.extend([key(KeyCode::Home), key(KeyCode::Right)]);
+    keys.extend(typed("#"));
+    keys.extend([key(KeyCode::End)]);
+    keys.extend(typed("!"));
+    assert_eq!(edit(&keys), ("r#ust lang!".to_string(), 11));
+}
+
+#[test]
+fn altgr_characters_are_inserted() {
+    // AltGr arrives as Ctrl+Alt; Ctrl or Alt alone is a shortcut
+    let altgr = KeyModifiers::CONTROL | KeyModifiers::ALT;
+    let keys = [
+        KeyEvent::new(KeyCode::Char('@'), altgr),
+        KeyEvent::new(KeyCode::Char('€'), altgr | KeyModifiers::SHIFT),
+        KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+        KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL),
+    ];
+    assert_eq!(edit(&keys), ("@€".to_string(), 4));
+}
+
+#[test]
+fn backspace_and_delete_remove_one_character() {
+    let mut keys = typed("rust");
+    keys.extend([key(KeyCode::Left), key(KeyCode::Left)]);
+    keys.extend([key(KeyCode::Backspace), key(KeyCode::Delete)]);
+    assert_eq!(edit(&keys), ("rt".to_string(), 1));
+
+    let mut keys = typed("rust");
+    keys.extend([ctrl('a'), ctrl('d'), ctrl('e'), ctrl('h')]);
+    assert_eq!(edit(&keys), ("us".to_string(), 2));
+}
+
+#[test]
+fn words_are_skipped_and_deleted() {
+    let mut keys = typed("rust async, await");
+    keys.push(alt('b'));
+    assert_eq!(edit(&keys).1, 12);
+    keys.push(alt('b'));
+    assert_eq!(edit(&keys).1, 5);
+    keys.push(alt('f'));
+    assert_eq!(edit(&keys).1, 10);
+    keys.push(KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL));
+    assert_eq!(edit(&keys).1, 17);
+
+    let mut keys = typed("rust async, await");
+    keys.push(ctrl('w'));
+    assert_eq!(edit(&keys), ("rust async, ".to_string(), 12));
+    keys.push(ctrl('w'));
+    assert_eq!(edit(&keys), ("rust ".to_string(), 5));
+}
+
+#[test]
+fn kill_keys_delete_to_either_end() {
+    let mut keys = typed("rust programming");
+    keys.extend([alt('b'), ctrl('k')]);
+    assert_eq!(edit(&keys), ("rust ".to_string(), 5));
+
+    let mut keys = typed("rust programming");
+    keys.extend([alt('b'), ctrl('u')]);
+    assert_eq!(edit(&keys), ("programming".to_string(), 0));
+}
+
+#[test]
+fn grapheme_clusters_move_and_delete_as_one() {
+    // "e" with a combining acute accent, and a family emoji joined by ZWJs
+    let mut keys = typed("cafe\u{301} 👨‍👩‍👧");
+    keys.push(key(KeyCode::Backspace));
+    assert_eq!(edit(&keys).0, "cafe\u{301} ");
+
+    keys.extend([key(KeyCode::Left), key(KeyCode::Left)]);
+    keys.push(key(KeyCode::Delete));
+    assert_eq!(edit(&keys), ("caf ".to_string(), 3));
+}
+
+#[test]
+fn cursor_is_kept_on_a_character_boundary() {
+    assert_eq!(clamp("日本", 4), 3);
+    assert_eq!(clamp("日本", 100), 6);
+    assert_eq!(clamp("", 3), 0);
+
+    let mut text = "日本".to_string();
+    let mut cursor = 1;
+    handle_key(
+        &mut text,
+        &mut cursor,
+        key(KeyCode::Char('x')),
+        &Keymap::default(),
+    );
+    assert_eq!(text, "x日本");
+}
+
+#[test]
+fn long_input_scrolls_to_keep_the_cursor_visible() {
+    let text = "abcdefghij";
+    assert_eq!(visible_window(text, 0, 20), ("abcdefghij", 0));
+    assert_eq!(visible_window(text, 0, 4), ("abcd", 0));
+    // At the end the cursor needs a column after the last character
+    assert_eq!(visible_window(text, 10, 4), ("hij", 3));
+    assert_eq!(visible_window(text, 5, 4), ("cdef", 3));
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    let text = "日本語テキスト";
+    assert_eq!(visible_window(text, 6, 20), (text, 4));
+    assert_eq!(visible_window(text, text.len(), 7), ("キスト", 6));
+    // A wide character that does not fit is left out
+    assert_eq!(visible_window(text, 0, 5), ("日本", 0));
+}
@@ -0,0 +1,307 @@
+//! Search history recording, recall and fuzzy search
+
+mod common;
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use common::{ctrl, key};
+use crossterm::event::KeyCode;
+use search_in_terminal::{
+    core::{
+        app::Command,
+        history::{fuzzy_score, History, HistoryEntry},
+    },
+    App,
+};
+
+fn history_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("history")
+        .join(format!("{}.jsonl", name))
+}
+
+/// An empty history in a fresh file
+fn history(name: &str, max_entries: usize, dedupe: bool) -> History {
+    let path = history_path(name);
+    let _ = fs::remove_file(&path);
+    History::load(path, max_entries, dedupe)
+}
+
+/// A history file holding `queries`, oldest first, submitted days ago
+fn saved_history(name: &str, queries: &[&str]) -> History {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let content: String = queries
+        .iter()
+        .enumerate()
+        .map(|(i, query)| {
+            let entry = HistoryEntry {
+                query: query.to_string(),
+                engine: "Google".to_string(),
+                timestamp: now - (queries.len() - i) as u64 * 24 * 60 * 60,
+            };
+            serde_json::to_string(&entry).unwrap() + "\n"
+        })
+        .collect();
+    let path = history_path(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    History::load(path, 100, true)
+}
+
+fn setup() {
+    let config = format!(
+        "[search]\nprefetch = false\n\n[engine]\nfavor = \"google\"\n\n\
+         [history]\npath = {:?}\n",
+        history_path("app").display().to_string()
+    );
+    common::use_config("history", &config);
+}
+
+/// An app in input mode with its own history file
+fn app(name: &str, queries: &[&str]) -> App {
+    setup();
+    let mut app = App::new();
+    app.history = saved_history(name, queries);
+    app
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_key(key(KeyCode::Char(c)));
+    }
+}
+
+fn queries(entries: &[HistoryEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.query.as_str()).collect()
+}
+
+#[test]
+fn queries_are_saved_with_engine_and_time() {
+    let mut history = history("saved", 100, true);
+    history.add("  rust lang ", "Google").unwrap();
+    history.add("", "Google").unwrap();
+    history.add("tokio", "All").unwrap();
+
+    let reloaded = History::load(history_path("saved"), 100, true);
+    assert_eq!(reloaded.entries(), history.entries());
+    assert_eq!(queries(reloaded.entries()), ["rust lang", "tokio"]);
+    assert_eq!(reloaded.entries()[1].engine, "All");
+    assert!(reloaded.entries()[0].age() < 60);
+    assert_eq!(reloaded.get(0).unwrap().query, "tokio");
+    assert_eq!(reloaded.get(2), None);
+}
+
+#[test]
+fn repeated_queries_move_to_the_end_when_deduplicated() {
+    let mut history = history("dedupe", 100, true);
+    for query in ["rust", "go", "rust"] {
+        history.add(query, "Google").unwrap();
+    }
+    assert_eq!(queries(history.entries()), ["go", "rust"]);
+
+    let mut history = self::history("duplicates", 100, false);
+    for query in ["rust", "go", "rust"] {
+        history.add(query, "Google").unwrap();
+    }
+    assert_eq!(queries(history.entries()), ["rust", "go", "rust"]);
+}
+
+#[test]
+fn oldest_entries_are_dropped_beyond_the_maximum() {
+    let mut history = history("max", 2, true);
+    for query in ["one", "two", "three"] {
+        history.add(query, "Bing").unwrap();
+    }
+    assert_eq!(queries(history.entries()), ["two", "three"]);
+}
+
+#[test]
+fn concurrent_sessions_keep_each_others_queries() {
+    let mut first = history("sessions", 100, true);
+    let mut second = History::load(history_path("sessions"), 100, true);
+    first.add("from first", "Google").unwrap();
+    second.add("from second", "Bing").unwrap();
+    assert_eq!(queries(second.entries()), ["from first", "from second"]);
+}
+
+#[test]
+fn unreadable_lines_are_skipped() {
+    let path = history_path("corrupt");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(
+        &path,
+        "{\"query\":\"ok\",\"engine\":\"Google\",\"timestamp\":1}\nnot json\n",
+    )
+    .unwrap();
+    assert_eq!(queries(History::load(path, 100, true).entries()), ["ok"]);
+}
+
+#[test]
+fn fuzzy_matches_prefer_consecutive_characters_and_word_starts() {
+    assert_eq!(fuzzy_score("xyz", "rust"), None);
+    assert_eq!(fuzzy_score("", "rust"), Some(0));
+    assert!(fuzzy_score("RB", "rust book").is_some());
+    assert!(fuzzy_score("rust", "rust book") > fuzzy_score("rust", "r u s t"));
+    assert!(fuzzy_score("bo", "rust book") > fuzzy_score("bo", "rust about"));
+
+    let history = saved_history(
+        "fuzzy",
+        &["rust book", "about rust", "ruby on rails", "rust book"],
+    );
+    let matches: Vec<_> = history
+        .search("rb")
+        .into_iter()
+        .map(|entry| entry.query.as_str())
+        .collect();
+    assert_eq!(matches, ["rust book", "ruby on rails"]);
+    assert_eq!(history.search("").len(), 3);
+}
+
+#[test]
+fn up_and_down_recall_history_and_restore_the_draft() {
+    let mut app = app("recall", &["first", "second"]);
+    type_text(&mut app, "draft");
+
+    app.handle_key(key(KeyCode::Up));
+    assert_eq!(app.input, "second");
+    assert_eq!(app.cursor, 6);
+    app.handle_key(key(KeyCode::Up));
+    app.handle_key(key(KeyCode::Up));
+    assert_eq!(app.input, "first");
+
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.input, "second");
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.input, "draft");
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.input, "draft");
+
+    // An edited entry becomes the new draft
+    app.handle_key(ctrl('p'));
+    type_text(&mut app, "!");
+    app.handle_key(key(KeyCode::Up));
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.input, "second!");
+}
+
+/// Wait for the app to save `expected` to its history file in the background
+async fn saved_queries(app: &App, expected: &[&str]) -> Vec<String> {
+    let mut saved = Vec::new();
+    for _ in 0..50 {
+        let history = History::load(app.history.path().to_path_buf(), 100, true);
+        saved = queries(history.entries())
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if saved == expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    saved
+}
+
+#[tokio::test]
+async fn submitted_queries_are_recorded_unless_incognito() {
+    let mut app = app("submit", &[]);
+    type_text(&mut app, "rust history");
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Command::Search);
+    assert_eq!(app.history.get(0).unwrap().query, "rust history");
+    assert_eq!(app.history.get(0).unwrap().engine, "Google");
+    assert_eq!(
+        saved_queries(&app, &["rust history"]).await,
+        ["rust history"]
+    );
+
+    app.input_mode = false;
+    app.handle_key(key(KeyCode::Char('p')));
+    assert!(app.incognito);
+    app.handle_key(key(KeyCode::Char('i')));
+    type_text(&mut app, " secret");
+    app.handle_key(key(KeyCode::Enter));
+    assert_eq!(queries(app.history.entries()), ["rust history"]);
+    assert_eq!(
+        saved_queries(&app, &["rust history"]).await,
+        ["rust history"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn history_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut history = history("private", 100, true);
+    history.add("private query", "Google").unwrap();
+
+    let mode = fs::metadata(history.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // The file is written through a temporary file renamed into place
+    let leftovers: Vec<_> = fs::read_dir(history.path().parent().unwrap())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with("private.") && name.ends_with(".tmp")
+        })
+        .collect();
+    assert!(leftovers.is_empty());
+}
+
+#[tokio::test]
+async fn ctrl_r_searches_the_history() {
+    let mut app = app("popup", &["rust book", "tokio tutorial", "ruby on rails"]);
+    type_text(&mut app, "draft");
+    app.handle_key(ctrl('r'));
+    assert!(app.history_search.is_some());
+
+    type_text(&mut app, "rb");
+    let matches: Vec<_> = app
+        .history_matches()
+        .into_iter()
+        .map(|entry| entry.query.clone())
+        .collect();
+    assert_eq!(matches, ["rust book", "ruby on rails"]);
+    assert_eq!(app.input, "draft");
+
+    // Ctrl-R again moves on to the next match, Tab copies it for editing
+    app.handle_key(ctrl('r'));
+    assert_eq!(app.handle_key(key(KeyCode::Tab)), Command::None);
+    assert_eq!(app.history_search, None);
+    assert_eq!(app.input, "ruby on rails");
+
+    app.handle_key(ctrl('r'));
+    app.handle_key(key(KeyCode::Esc));
+    assert_eq!(app.history_search, None);
+    assert!(app.input_mode);
+    assert_eq!(app.input, "ruby on rails");
+
+    app.handle_key(ctrl('r'));
+    type_text(&mut app, "tok");
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Command::Search);
+    assert_eq!(app.input, "tokio tutorial");
+    assert_eq!(app.history.get(0).unwrap().query, "tokio tutorial");
+}
+
+#[test]
+fn snapshot_history_popup() {
+    let mut app = app(
+        "snapshot",
+        &["rust book", "tokio tutorial", "ruby on rails"],
+    );
+    app.handle_key(ctrl('r'));
+    type_text(&mut app, "r");
+    app.handle_key(key(KeyCode::Down));
+
+    let mut terminal = common::draw(&mut app, 80, 24);
+    terminal.backend_mut().assert_cursor_position((4, 5));
+    insta::assert_snapshot!("history_popup_80x24", terminal.backend());
+}
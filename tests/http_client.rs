@@ -0,0 +1,22 @@
+//! Connection reuse of the pooled HTTP clients
+
+mod common;
+
+use common::SERVER;
+use search_in_terminal::search::engine::SearchEngine;
+
+fn setup() {
+    common::mock_config("http_client", "[search]\nuser_agents = [\"st-test\"]\n");
+}
+
+#[tokio::test]
+async fn searches_reuse_the_engine_connection() {
+    setup();
+    let engine = SearchEngine::favor("bing");
+    for start in [0, 10, 20] {
+        engine.search("keep alive", start).await.unwrap();
+    }
+
+    assert_eq!(SERVER.hits("keep alive"), 3);
+    assert_eq!(SERVER.connections(), 1);
+}
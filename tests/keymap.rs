@@ -0,0 +1,349 @@
+//! Key chords, the default keymap and the help overlay generated from it
+
+mod common;
+
+use common::{ctrl, key};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use search_in_terminal::{
+    core::{
+        app::Command,
+        config::KeysConfig,
+        editor::Edit,
+        keymap::{Action, KeyChord, Keymap, Mode},
+    },
+    App,
+};
+
+fn setup() {
+    let history = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("keymap/history.jsonl");
+    let config = format!(
+        "[history]\npath = {:?}\n\n[suggest]\nenabled = false\n\n\
+         [keys.results]\nx = \"quit\"\n",
+        history.display().to_string()
+    );
+    common::use_config("keymap", &config);
+}
+
+fn chord(text: &str) -> KeyChord {
+    text.parse().unwrap()
+}
+
+fn keymap(config: &str) -> Keymap {
+    let config: KeysConfig = toml::from_str(config).unwrap();
+    Keymap::from_config(&config).unwrap()
+}
+
+fn config_error(config: &str) -> String {
+    let config: KeysConfig = toml::from_str(config).unwrap();
+    Keymap::from_config(&config).unwrap_err().to_string()
+}
+
+/// An app in results mode
+fn results_app() -> App {
+    let mut app = App::new();
+    app.input_mode = false;
+    app
+}
+
+#[test]
+fn chords_are_parsed_and_displayed() {
+    for (text, code, modifiers, shown) in [
+        (
+            "ctrl+r",
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+            "Ctrl+R",
+        ),
+        (
+            "Ctrl+R",
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+            "Ctrl+R",
+        ),
+        ("alt+b", KeyCode::Char('b'), KeyModifiers::ALT, "Alt+b"),
+        ("G", KeyCode::Char('G'), KeyModifiers::NONE, "G"),
+        ("?", KeyCode::Char('?'), KeyModifiers::NONE, "?"),
+        (
+            "ctrl++",
+            KeyCode::Char('+'),
+            KeyModifiers::CONTROL,
+            "Ctrl++",
+        ),
+        ("space", KeyCode::Char(' '), KeyModifiers::NONE, "Space"),
+        (
+            "shift+tab",
+            KeyCode::BackTab,
+            KeyModifiers::NONE,
+            "Shift+Tab",
+        ),
+        (
+            "PageDown",
+            KeyCode::PageDown,
+            KeyModifiers::NONE,
+            "PageDown",
+        ),
+        (
+            "ctrl+left",
+            KeyCode::Left,
+            KeyModifiers::CONTROL,
+            "Ctrl+Left",
+        ),
+        ("f1", KeyCode::F(1), KeyModifiers::NONE, "F1"),
+        ("esc", KeyCode::Esc, KeyModifiers::NONE, "Esc"),
+    ] {
+        let parsed = chord(text);
+        assert_eq!(parsed, KeyChord { code, modifiers }, "{}", text);
+        assert_eq!(parsed.to_string(), shown, "{}", text);
+    }
+
+    for invalid in ["", "hyper+x", "ctrl+", "f13", "pgdn"] {
+        assert!(invalid.parse::<KeyChord>().is_err(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn shift_is_part_of_the_character() {
+    let shifted = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+    assert_eq!(KeyChord::from_event(shifted), chord("G"));
+
+    let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+    assert_eq!(KeyChord::from_event(back_tab), chord("shift+tab"));
+
+    let shift_up = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+    assert_eq!(KeyChord::from_event(shift_up), chord("shift+up"));
+
+    // Shift with a letter is the uppercase letter, so the chord can fire
+    assert_eq!(chord("shift+g"), chord("G"));
+    assert_eq!(chord("Alt+Shift+b"), chord("alt+B"));
+    assert_eq!(KeyChord::from_event(shifted), chord("shift+g"));
+    for invalid in ["shift+G", "shift+1", "shift+?", "ctrl+shift+r"] {
+        let error = invalid.parse::<KeyChord>().unwrap_err().to_string();
+        assert!(error.contains("shifted character"), "{}", error);
+    }
+}
+
+#[test]
+fn actions_are_named() {
+    for action in Action::ALL {
+        assert_eq!(action.name().parse::<Action>().unwrap(), action);
+    }
+    assert_eq!(
+        "delete_to_start".parse::<Action>().unwrap(),
+        Action::Edit(Edit::DeleteToStart)
+    );
+    assert!("launch_missiles".parse::<Action>().is_err());
+}
+
+#[test]
+fn default_keymap_looks_up_actions_per_mode() {
+    let keymap = Keymap::default();
+    let shifted_r = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT);
+
+    assert_eq!(
+        keymap.action(Mode::Results, key(KeyCode::Char('j'))),
+        Some(Action::SelectNext)
+    );
+    assert_eq!(
+        keymap.action(Mode::Results, shifted_r),
+        Some(Action::Refresh)
+    );
+    assert_eq!(
+        keymap.action(Mode::Results, key(KeyCode::Char('r'))),
+        Some(Action::ClearResults)
+    );
+    assert_eq!(keymap.action(Mode::Input, key(KeyCode::Char('j'))), None);
+    assert_eq!(
+        keymap.action(Mode::Input, ctrl('w')),
+        Some(Action::Edit(Edit::DeleteWordBackward))
+    );
+    assert_eq!(
+        keymap.chords(Mode::Results, Action::SelectNext),
+        [chord("j"), chord("down")]
+    );
+}
+
+#[test]
+fn help_lists_every_binding_grouped_by_mode() {
+    let keymap = Keymap::default();
+    let help = keymap.help();
+
+    let modes: Vec<Mode> = help.iter().map(|(mode, _)| *mode).collect();
+    assert_eq!(modes, Mode::ALL);
+
+    let results = &help[1].1;
+    assert!(results.contains(&("j, Down".to_string(), "Select the next item")));
+    assert!(results.contains(&("R".to_string(), "Refresh results, bypassing the cache")));
+    let input = &help[2].1;
+    assert!(input.contains(&(
+        "Alt+b, Ctrl+Left, Alt+Left".to_string(),
+        "Move to the previous word"
+    )));
+}
+
+#[test]
+fn help_and_hints_follow_rebound_keys() {
+    let mut keymap = Keymap::default();
+    keymap.unbind(Mode::Results, chord("j"));
+    keymap.bind(Mode::Results, chord("n"), Action::SelectNext);
+    keymap.unbind(Mode::Results, chord("?"));
+
+    let help = keymap.help();
+    assert!(help[1]
+        .1
+        .contains(&("Down, n".to_string(), "Select the next item")));
+    assert!(!help[1]
+        .1
+        .iter()
+        .any(|(_, description)| *description == "Show this help"));
+
+    let hint = keymap.hint(Mode::Results);
+    assert!(hint.starts_with("F1: Help | Down/k: Navigate"), "{}", hint);
+}
+
+#[test]
+fn question_mark_opens_a_scrollable_help() {
+    setup();
+    let mut app = results_app();
+    let question = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT);
+    assert_eq!(app.handle_key(question), Command::None);
+    assert!(app.help.is_some());
+
+    // Keys scroll the overlay instead of reaching the results
+    common::draw(&mut app, 80, 24);
+    assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Command::None);
+    assert!(app.help.is_none());
+
+    app.handle_key(question);
+    common::draw(&mut app, 80, 24);
+    app.handle_key(key(KeyCode::Char('j')));
+    app.handle_key(key(KeyCode::Char('j')));
+    assert_eq!(app.help.as_ref().unwrap().scroll, 2);
+    app.handle_key(key(KeyCode::PageDown));
+    let page = app.help.as_ref().unwrap().page;
+    assert_eq!(page, 20);
+    assert_eq!(app.help.as_ref().unwrap().scroll, 22);
+
+    // Scrolling past the end stops at the last page once drawn
+    app.handle_key(key(KeyCode::End));
+    common::draw(&mut app, 80, 24);
+    let bottom = app.help.as_ref().unwrap().scroll;
+    assert!(bottom > 22 && bottom < usize::MAX);
+    app.handle_key(key(KeyCode::Char('k')));
+    assert_eq!(app.help.as_ref().unwrap().scroll, bottom - 1);
+
+    app.handle_key(key(KeyCode::Esc));
+    assert!(app.help.is_none());
+}
+
+#[test]
+fn f1_opens_the_help_while_typing() {
+    setup();
+    let mut app = App::new();
+    app.handle_key(key(KeyCode::Char('?')));
+    assert_eq!(app.input, "?");
+    assert!(app.help.is_none());
+
+    app.handle_key(key(KeyCode::F(1)));
+    assert!(app.help.is_some());
+    app.handle_key(key(KeyCode::F(1)));
+    assert!(app.help.is_none());
+    assert!(app.input_mode);
+}
+
+#[test]
+fn snapshot_help() {
+    setup();
+    let mut app = results_app();
+    app.show_help();
+    insta::assert_snapshot!("help_80x24", common::draw(&mut app, 80, 24).backend());
+
+    app.handle_key(key(KeyCode::End));
+    insta::assert_snapshot!("help_end_80x24", common::draw(&mut app, 80, 24).backend());
+}
+
+#[test]
+fn keys_table_overrides_the_preset() {
+    let keymap = keymap(
+        "[results]\n\"n\" = \"select_next\"\nj = \"none\"\n\"ctrl+q\" = \"quit\"\n\n\
+         [input]\n\"ctrl+y\" = \"move_to_end\"\n",
+    );
+    assert_eq!(
+        keymap.chords(Mode::Results, Action::SelectNext),
+        [chord("down"), chord("n")]
+    );
+    assert_eq!(
+        keymap.chords(Mode::Results, Action::Quit),
+        [chord("q"), chord("ctrl+q")]
+    );
+    assert_eq!(
+        keymap.action(Mode::Input, ctrl('y')),
+        Some(Action::Edit(Edit::MoveToEnd))
+    );
+    // Rebinding a chord replaces what the preset bound to it
+    let keymap = self::keymap("[results]\nq = \"clear_results\"\n");
+    assert!(keymap.chords(Mode::Results, Action::Quit).is_empty());
+    assert_eq!(
+        keymap.action(Mode::Results, key(KeyCode::Char('q'))),
+        Some(Action::ClearResults)
+    );
+}
+
+#[test]
+fn presets_add_vim_and_emacs_bindings() {
+    let vim = keymap("preset = \"vim\"\n");
+    assert_eq!(
+        vim.action(Mode::Results, key(KeyCode::Char('/'))),
+        Some(Action::EditInput)
+    );
+    assert_eq!(vim.action(Mode::Help, ctrl('d')), Some(Action::PageDown));
+    assert_eq!(
+        vim.action(Mode::Results, key(KeyCode::Char('j'))),
+        Some(Action::SelectNext)
+    );
+
+    let emacs = keymap("preset = \"emacs\"\n");
+    assert_eq!(
+        emacs.action(Mode::Results, ctrl('n')),
+        Some(Action::SelectNext)
+    );
+    assert_eq!(
+        emacs.action(Mode::Input, ctrl('g')),
+        Some(Action::StopEditing)
+    );
+    assert_eq!(
+        emacs.action(
+            Mode::Help,
+            KeyEvent::new(KeyCode::Char('v'), KeyModifiers::ALT)
+        ),
+        Some(Action::PageUp)
+    );
+
+    // Plain characters stay free for typing in the input with every preset
+    for preset in [vim, emacs, Keymap::default()] {
+        for c in ('a'..='z').chain('A'..='Z').chain(['/', '?', ' ']) {
+            assert_eq!(preset.action(Mode::Input, key(KeyCode::Char(c))), None);
+        }
+    }
+}
+
+#[test]
+fn invalid_keys_tables_are_rejected() {
+    assert!(config_error("preset = \"helix\"\n").contains("Unknown key preset: helix"));
+    assert!(config_error("[results]\n\"hyper+x\" = \"quit\"\n")
+        .contains("Invalid key in [keys.results]: \"hyper+x\""));
+    assert!(config_error("[input]\n\"ctrl+y\" = \"yank\"\n")
+        .contains("Unknown action for Ctrl+Y in [keys.input]: yank"));
+}
+
+#[test]
+fn configured_keys_drive_the_app_and_its_help() {
+    setup();
+    let mut app = results_app();
+    assert_eq!(app.handle_key(key(KeyCode::Char('x'))), Command::Quit);
+    assert!(app
+        .keymap
+        .help()
+        .iter()
+        .any(|(mode, entries)| *mode == Mode::Results
+            && entries.contains(&("q, x".to_string(), "Quit"))));
+}
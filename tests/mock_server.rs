@@ -0,0 +1,190 @@
+//! Network behaviour of the engines against the local mock search server
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::SERVER;
+use search_in_terminal::{search::engine::SearchEngine, SearchError};
+
+const MAX_RETRIES: usize = 3;
+const DEADLINE: Duration = Duration::from_secs(2);
+
+fn setup() {
+    common::mock_config(
+        "mock_server",
+        &format!(
+            "[search]\nmax_retries = {}\nmax_jitter = 5\n\
+             request_timeout = 1\nresponse_timeout = 1\n\n\
+             [retry]\nbackoff_base = 10\nbackoff_max = 40\ndeadline = {}\n",
+            MAX_RETRIES,
+            DEADLINE.as_secs()
+        ),
+    );
+}
+
+async fn search_error(engine: &str, query: &str) -> SearchError {
+    let error = SearchEngine::favor(engine)
+        .search(query, 0)
+        .await
+        .expect_err("search should fail");
+    error
+        .downcast::<SearchError>()
+        .expect("error should be a SearchError")
+}
+
+#[tokio::test]
+async fn every_engine_parses_mock_results() {
+    setup();
+    for engine in ["google", "bing", "duckduckgo"] {
+        let query = format!("rust {}", engine);
+        let results = SearchEngine::favor(engine).search(&query, 0).await.unwrap();
+        assert_eq!(results.len(), 10, "{}", engine);
+        assert_eq!(results[0].title, format!("{} result 1", query));
+
+        let next = SearchEngine::favor(engine)
+            .search(&query, 10)
+            .await
+            .unwrap();
+        assert_eq!(next[0].title, format!("{} result 11", query));
+    }
+}
+
+#[tokio::test]
+async fn forbidden_is_reported_as_blocked_without_retrying() {
+    setup();
+    let error = search_error("google", "status-403").await;
+    assert_eq!(
+        error.to_string(),
+        "Search engine blocked the request: HTTP 403 Forbidden"
+    );
+    assert_eq!(SERVER.hits("status-403"), 1);
+}
+
+#[tokio::test]
+async fn too_many_requests_honors_retry_after_until_the_deadline() {
+    setup();
+    let started = Instant::now();
+    let error = search_error("bing", "status-429").await;
+    assert!(
+        matches!(
+            error,
+            SearchError::RateLimited {
+                retry_after: Some(retry_after)
+            } if retry_after == Duration::from_secs(1)
+        ),
+        "{:?}",
+        error
+    );
+    // One retry after waiting a second; a second wait would pass the deadline
+    assert_eq!(SERVER.hits("status-429"), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn server_errors_are_reported_with_status() {
+    setup();
+    let error = search_error("duckduckgo", "status-500").await;
+    assert_eq!(
+        error.to_string(),
+        "Network error: HTTP error: 500 Internal Server Error"
+    );
+}
+
+#[tokio::test]
+async fn captcha_pages_are_reported_as_blocked_with_reason() {
+    setup();
+    for (engine, reason) in [
+        ("google", "Google reCAPTCHA challenge"),
+        ("bing", "Bing challenge page"),
+        ("duckduckgo", "DuckDuckGo anomaly page"),
+    ] {
+        let error = search_error(engine, &format!("captcha {}", engine)).await;
+        assert!(
+            matches!(&error, SearchError::Blocked { reason: actual } if actual.starts_with(reason)),
+            "{}: {:?}",
+            engine,
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn consent_interstitials_are_accepted_and_remembered() {
+    setup();
+    for engine in ["google", "bing"] {
+        let query = format!("consent {}", engine);
+        let results = SearchEngine::favor(engine).search(&query, 0).await.unwrap();
+        assert_eq!(results.len(), 10, "{}", engine);
+        // The consent page, then the results with the consent cookie
+        assert_eq!(SERVER.hits(&query), 2, "{}", engine);
+
+        let query = format!("consent {} again", engine);
+        SearchEngine::favor(engine).search(&query, 0).await.unwrap();
+        assert_eq!(SERVER.hits(&query), 1, "{}", engine);
+    }
+}
+
+#[tokio::test]
+async fn google_sorry_redirect_is_reported_as_blocked() {
+    setup();
+    let error = search_error("google", "sorry").await;
+    assert!(
+        matches!(&error, SearchError::Blocked { reason } if reason.contains("/sorry/")),
+        "{:?}",
+        error
+    );
+    // Blocks are not retried
+    assert_eq!(SERVER.hits("sorry"), 1);
+}
+
+#[tokio::test]
+async fn results_mentioning_blocks_are_not_blocked() {
+    setup();
+    let results = SearchEngine::favor("google")
+        .search("blocked CAPTCHA ad-blocker", 0)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 10);
+}
+
+#[tokio::test]
+async fn empty_page_is_reported_as_no_results() {
+    setup();
+    let error = search_error("bing", "empty").await;
+    assert!(matches!(error, SearchError::NoResults), "{:?}", error);
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_with_backoff() {
+    setup();
+    let results = SearchEngine::favor("google")
+        .search("flaky-2 recover", 0)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 10);
+    assert_eq!(SERVER.hits("flaky-2 recover"), 3);
+}
+
+#[tokio::test]
+async fn retries_stop_after_max_retries() {
+    setup();
+    let error = search_error("duckduckgo", "flaky-5 give up").await;
+    assert_eq!(
+        error.to_string(),
+        "Network error: HTTP error: 503 Service Unavailable"
+    );
+    assert_eq!(SERVER.hits("flaky-5 give up"), MAX_RETRIES);
+}
+
+#[tokio::test]
+async fn slow_responses_time_out_within_the_deadline() {
+    setup();
+    let started = Instant::now();
+    let error = search_error("google", "timeout").await;
+    assert!(matches!(error, SearchError::Timeout), "{:?}", error);
+    // Two one-second attempts use up the whole deadline
+    assert_eq!(SERVER.hits("timeout"), 2);
+    assert!(started.elapsed() < DEADLINE + Duration::from_millis(500));
+}
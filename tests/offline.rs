@@ -0,0 +1,88 @@
+//! Offline mode answering only from the results cache
+
+mod common;
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use common::SERVER;
+use search_in_terminal::{
+    search::engine::{CacheMode, PageSource, SearchEngine},
+    App, SearchError,
+};
+
+fn cache_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("offline/cache")
+}
+
+fn setup() {
+    let _ = fs::remove_dir_all(cache_dir());
+    // Entries expire after a second, so tests can see them go stale
+    common::mock_config(
+        "offline",
+        &format!(
+            "[cache]\ntime_to_live = 1\ndisk = true\ndisk_dir = {:?}\n",
+            cache_dir().display().to_string()
+        ),
+    );
+}
+
+#[tokio::test]
+async fn uncached_searches_fail_without_touching_the_network() {
+    setup();
+    let error = SearchEngine::favor("google")
+        .search_page("never searched", 0, CacheMode::Offline)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error.downcast_ref(), Some(SearchError::NotCached)),
+        "{:?}",
+        error
+    );
+    assert_eq!(SERVER.hits("never searched"), 0);
+}
+
+#[tokio::test]
+async fn cached_searches_are_served_fresh_then_stale() {
+    setup();
+    let engine = SearchEngine::favor("bing");
+    let online = engine
+        .search_page("plane trip", 0, CacheMode::Normal)
+        .await
+        .unwrap();
+    assert_eq!(online.source, PageSource::Network);
+
+    let offline = engine
+        .search_page("plane trip", 0, CacheMode::Offline)
+        .await
+        .unwrap();
+    assert_eq!(offline.source, PageSource::Cache);
+    assert_eq!(offline.results, online.results);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let stale = engine
+        .search_page("plane trip", 0, CacheMode::Offline)
+        .await
+        .unwrap();
+    assert_eq!(stale.source, PageSource::Stale);
+    assert_eq!(stale.results, online.results);
+    assert_eq!(SERVER.hits("plane trip"), 1);
+}
+
+#[tokio::test]
+async fn offline_app_marks_stale_results() {
+    setup();
+    let engine = SearchEngine::favor("duckduckgo");
+    engine.search("rust offline", 0).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let mut app = App::new();
+    app.search_engine = engine;
+    app.offline = true;
+    app.input = "rust offline".to_string();
+    app.perform_search().await.unwrap();
+
+    assert_eq!(app.search_results.len(), 10);
+    assert!(app.stale);
+    assert_eq!(app.error_message, None);
+    assert_eq!(SERVER.hits("rust offline"), 1);
+}
@@ -0,0 +1,129 @@
+//! Offline parser tests against saved result pages
+//!
+//! Every `tests/fixtures/<engine>/<case>.html` page is parsed with the matching
+//! engine and compared with the golden output in `<case>.json`, which holds
+//! either `{"results": [...]}` or `{"error": "<message>"}`.
+//!
+//! Block and consent detection are checked against the same pages: only
+//! `captcha.html` must be recognized as a block page and only `consent.html`
+//! as a cookie consent interstitial.
+//!
+//! Run with `UPDATE_GOLDEN=1 cargo test --test parsers` to rewrite the golden
+//! files after an intentional parser change.
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use search_in_terminal::{search::engine::SearchEngine, SearchResult};
+
+/// Expected outcome of parsing a fixture page
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Golden {
+    Results(Vec<SearchResult>),
+    Error(String),
+}
+
+fn parse_fixture(engine: SearchEngine, html: &str) -> Golden {
+    match engine.parse(html) {
+        Ok(results) => Golden::Results(results),
+        Err(e) => Golden::Error(e.to_string()),
+    }
+}
+
+fn check_engine(name: &str) {
+    let engine = SearchEngine::favor(name);
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut pages: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    pages.sort();
+    assert!(!pages.is_empty(), "no fixtures in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for page in pages {
+        let html = fs::read_to_string(&page).unwrap();
+        let is_captcha = page.file_stem().is_some_and(|stem| stem == "captcha");
+        if engine.detect_block("", &html).is_some() != is_captcha {
+            failures.push(format!(
+                "{}: block detection expected {}",
+                page.display(),
+                is_captcha
+            ));
+        }
+        let is_consent = page.file_stem().is_some_and(|stem| stem == "consent");
+        if engine.detect_consent("", &html) != is_consent {
+            failures.push(format!(
+                "{}: consent detection expected {}",
+                page.display(),
+                is_consent
+            ));
+        }
+
+        let actual = parse_fixture(engine, &html);
+        let golden_path = page.with_extension("json");
+
+        if update {
+            let json = serde_json::to_string_pretty(&actual).unwrap();
+            fs::write(&golden_path, json + "\n").unwrap();
+            continue;
+        }
+
+        let expected: Golden = serde_json::from_str(
+            &fs::read_to_string(&golden_path)
+                .unwrap_or_else(|_| panic!("missing golden file {}", golden_path.display())),
+        )
+        .unwrap();
+        if actual != expected {
+            failures.push(format!(
+                "{}:\n  expected: {:?}\n  actual:   {:?}",
+                page.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn google_fixtures_match_golden() {
+    check_engine("google");
+}
+
+#[test]
+fn bing_fixtures_match_golden() {
+    check_engine("bing");
+}
+
+#[test]
+fn duckduckgo_fixtures_match_golden() {
+    check_engine("duckduckgo");
+}
+
+#[test]
+fn google_consent_redirect_is_detected() {
+    let engine = SearchEngine::favor("google");
+    assert!(engine.detect_consent("https://consent.google.com/ml?continue=x", ""));
+    assert!(!engine.detect_consent("https://www.google.com/search?q=consent", ""));
+}
+
+#[test]
+fn google_sorry_redirect_is_detected() {
+    let engine = SearchEngine::favor("google");
+    let reason = engine.detect_block("https://www.google.com/sorry/index?continue=x", "");
+    assert!(reason.is_some_and(|reason| reason.contains("/sorry/")));
+    assert_eq!(
+        engine.detect_block("https://www.google.com/search?q=sorry", ""),
+        None
+    );
+}
@@ -0,0 +1,83 @@
+//! Routing searches through configured proxies, using the mock server as proxy
+
+mod common;
+
+use common::SERVER;
+use std::{fs, path::Path};
+
+use search_in_terminal::{core::config::Config, search::engine::SearchEngine, CONFIG};
+
+fn setup() {
+    // Google and Bing point at a host that only resolves through the proxy,
+    // DuckDuckGo bypasses the proxy and talks to the server directly
+    common::mock_config(
+        "proxy",
+        &format!(
+            "[engine.base_urls]\ngoogle = \"http://search.invalid/google\"\n\
+             bing = \"http://search.invalid/bing\"\nduckduckgo = \"{}\"\n\n\
+             [proxy]\nurl = \"http://{}\"\nusername = \"alice\"\npassword = \"secret\"\n\n\
+             [proxy.engines.duckduckgo]\nurl = \"direct\"\n",
+            SERVER.base_url("duckduckgo"),
+            SERVER.addr()
+        ),
+    );
+}
+
+#[tokio::test]
+async fn searches_go_through_the_proxy_with_credentials() {
+    setup();
+    let results = SearchEngine::favor("google")
+        .search("via proxy", 0)
+        .await
+        .unwrap();
+    assert_eq!(results[0].title, "via proxy result 1");
+
+    assert!(SERVER
+        .proxied()
+        .iter()
+        .any(|url| url.starts_with("http://search.invalid/google/search?q=via%20proxy")));
+    // "alice:secret" in base64
+    assert_eq!(
+        SERVER.proxy_authorization().as_deref(),
+        Some("Basic YWxpY2U6c2VjcmV0")
+    );
+}
+
+#[tokio::test]
+async fn direct_engines_bypass_the_proxy() {
+    setup();
+    let results = SearchEngine::favor("duckduckgo")
+        .search("no proxy", 0)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 10);
+    assert_eq!(SERVER.hits("no proxy"), 1);
+    assert!(!SERVER
+        .proxied()
+        .iter()
+        .any(|url| url.contains("no%20proxy")));
+}
+
+#[test]
+fn debug_output_hides_the_password() {
+    setup();
+    let debug = format!("{:?}", CONFIG.proxy.global);
+    assert!(debug.contains("alice"), "{}", debug);
+    assert!(debug.contains("<redacted>"), "{}", debug);
+    assert!(!debug.contains("secret"), "{}", debug);
+}
+
+#[test]
+fn malformed_proxy_settings_fail_to_load() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("proxy/malformed.toml");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(
+        &path,
+        "[proxy]\nurl = \"socks5h://127.0.0.1:9050\"\ntor = \"yes\"\n",
+    )
+    .unwrap();
+
+    let error = format!("{:#}", Config::load(&path).unwrap_err());
+    assert!(error.contains("Failed to parse config file"), "{}", error);
+    assert!(error.contains("[proxy]"), "{}", error);
+}
@@ -0,0 +1,107 @@
+//! Token-bucket rate limiting per engine
+
+use std::time::{Duration, Instant};
+
+use search_in_terminal::{
+    core::config::RateLimitConfig,
+    search::rate_limit::{RateLimiter, TokenBucket},
+};
+
+#[test]
+fn burst_is_available_immediately() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(3, 60, now);
+    for _ in 0..3 {
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+    }
+    assert_eq!(bucket.reserve(now), Duration::from_secs(1));
+}
+
+#[test]
+fn tokens_refill_at_the_configured_rate() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(1, 60, now);
+    assert_eq!(bucket.reserve(now), Duration::ZERO);
+    assert_eq!(bucket.available(now + Duration::from_millis(500)), 0.5);
+    assert_eq!(bucket.reserve(now + Duration::from_secs(1)), Duration::ZERO);
+}
+
+#[test]
+fn refill_is_capped_at_the_burst() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(2, 60, now);
+    let later = now + Duration::from_secs(60);
+    assert_eq!(bucket.available(later), 2.0);
+    assert_eq!(bucket.reserve(later), Duration::ZERO);
+    assert_eq!(bucket.reserve(later), Duration::ZERO);
+    assert_eq!(bucket.reserve(later), Duration::from_secs(1));
+}
+
+#[test]
+fn reservations_are_spaced_out() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(1, 120, now);
+    let waits: Vec<_> = (0..4).map(|_| bucket.reserve(now)).collect();
+    assert_eq!(
+        waits,
+        [
+            Duration::ZERO,
+            Duration::from_millis(500),
+            Duration::from_secs(1),
+            Duration::from_millis(1500),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn engines_have_separate_buckets() {
+    let config: RateLimitConfig =
+        toml::from_str("requests_per_minute = 1\nburst = 2\n\n[engines.bing]\nburst = 3\n")
+            .unwrap();
+    let limiter = RateLimiter::new(&config, 0);
+
+    limiter.acquire("Google").await;
+    assert!(!limiter.has_spare("google"));
+    limiter.acquire("Bing").await;
+    assert!(limiter.has_spare("bing"));
+    limiter.acquire("Bing").await;
+    assert!(!limiter.has_spare("bing"));
+    assert!(limiter.has_spare("DuckDuckGo"));
+}
+
+#[test]
+fn engine_overrides_fall_back_to_the_defaults() {
+    let config: RateLimitConfig =
+        toml::from_str("requests_per_minute = 30\n\n[engines.google]\nburst = 1\n").unwrap();
+    let google = config.for_engine("Google");
+    assert_eq!(google.requests_per_minute, 30);
+    assert_eq!(google.burst, 1);
+    assert_eq!(config.for_engine("bing").burst, 4);
+}
+
+#[tokio::test]
+async fn only_tokens_beyond_the_next_request_are_spare() {
+    let config: RateLimitConfig = toml::from_str("requests_per_minute = 1\nburst = 2\n").unwrap();
+    let limiter = RateLimiter::new(&config, 0);
+
+    assert!(limiter.has_spare("google"));
+    limiter.acquire("google").await;
+    assert!(!limiter.has_spare("google"));
+    // The last token of the burst still goes out without waiting
+    tokio::time::timeout(Duration::from_millis(100), limiter.acquire("google"))
+        .await
+        .unwrap();
+    assert!(!limiter.has_spare("google"));
+    assert!(limiter.has_spare("bing"));
+}
+
+#[test]
+fn available_tokens_go_negative_with_reservations() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket::new(1, 60, now);
+    assert_eq!(bucket.available(now), 1.0);
+    bucket.reserve(now);
+    bucket.reserve(now);
+    assert_eq!(bucket.available(now), -1.0);
+    assert_eq!(bucket.available(now + Duration::from_secs(1)), 0.0);
+}
@@ -0,0 +1,55 @@
+//! Recording HTTP exchanges against the local mock search server
+
+mod common;
+
+use std::{fs, path::Path};
+
+use search_in_terminal::search::{
+    engine::{Engine, Google},
+    transport::recording_path,
+};
+
+fn recordings_dir() -> &'static Path {
+    Path::new(concat!(env!("CARGO_TARGET_TMPDIR"), "/record/recordings"))
+}
+
+fn setup() {
+    common::mock_config(
+        "record",
+        &format!(
+            "[recording]\nmode = \"record\"\ndir = {:?}\n",
+            recordings_dir().display().to_string()
+        ),
+    );
+}
+
+#[tokio::test]
+async fn searches_are_recorded_to_disk() {
+    setup();
+    let results = Google.search("record me", 0).await.unwrap();
+    assert_eq!(results.len(), 10);
+
+    let path = recording_path(recordings_dir(), &Google.build_url("record me", 0));
+    let recording: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(recording["response"]["status"], 200);
+    assert!(recording["response"]["body"]
+        .as_str()
+        .unwrap()
+        .contains("record me result 1"));
+}
+
+#[tokio::test]
+async fn session_cookies_are_left_out_of_recordings() {
+    setup();
+    Google.search("private recording", 0).await.unwrap();
+
+    let path = recording_path(recordings_dir(), &Google.build_url("private recording", 0));
+    let content = fs::read_to_string(path).unwrap();
+    assert!(!content.contains("NID"), "{}", content);
+    assert!(
+        !content.to_lowercase().contains("set-cookie"),
+        "{}",
+        content
+    );
+}
@@ -0,0 +1,66 @@
+//! End-to-end search flows replayed from recorded HTTP exchanges
+//!
+//! The recordings in `tests/fixtures/recordings` have the same format as those
+//! written by `[recording] mode = "record"` and are served back here, so
+//! `App::perform_search` and the renderer run without network access.
+
+mod common;
+
+use search_in_terminal::{search::engine::SearchEngine, App};
+
+/// Point the global configuration at the recorded fixtures
+fn setup() {
+    let config = format!(
+        "[search]\nmax_retries = 1\nmax_jitter = 1\n\n\
+         [rate_limit]\nrequests_per_minute = 6000\nburst = 100\n\n\
+         [recording]\nmode = \"replay\"\ndir = {:?}\n",
+        common::fixture_path("recordings")
+    );
+    common::use_config("replay", &config);
+}
+
+#[tokio::test]
+async fn google_search_is_replayed_and_rendered() {
+    setup();
+    let mut app = App::new();
+    app.search_engine = SearchEngine::favor("google");
+    app.input = "rust programming".to_string();
+
+    app.perform_search().await.unwrap();
+
+    assert_eq!(app.error_message, None);
+    assert_eq!(app.search_results.len(), 3);
+    assert!(!app.input_mode);
+
+    let screen = common::render(&mut app, 100, 40);
+    assert!(screen.contains("1. Rust Programming Language"));
+    assert!(screen.contains("https://doc.rust-lang.org/book/"));
+    assert!(screen.contains("Found 3 results"));
+    assert!(screen.contains("(Page 1/1)"));
+}
+
+#[tokio::test]
+async fn every_engine_has_a_replayable_recording() {
+    setup();
+    for name in ["google", "bing", "duckduckgo"] {
+        let engine = SearchEngine::favor(name);
+        let results = engine.search("rust programming", 0).await.unwrap();
+        assert_eq!(results[0].url, "https://www.rust-lang.org/", "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn missing_recording_is_reported_as_error() {
+    setup();
+    let mut app = App::new();
+    app.input = "not recorded".to_string();
+
+    app.perform_search().await.unwrap();
+
+    assert!(app.search_results.is_empty());
+    let error = app.error_message.clone().unwrap();
+    assert!(error.contains("No recording for"), "{}", error);
+
+    let screen = common::render(&mut app, 100, 40);
+    assert!(screen.contains("Search failed"));
+}
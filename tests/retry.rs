@@ -0,0 +1,127 @@
+//! Retry policy decisions and `Retry-After` parsing
+
+use std::time::{Duration, Instant, SystemTime};
+
+use rand::{rngs::StdRng, SeedableRng};
+use search_in_terminal::{
+    core::config::RetryConfig,
+    search::{retry::RetryPolicy, transport::HttpResponse},
+    SearchError,
+};
+
+fn policy() -> RetryPolicy {
+    RetryPolicy::from_config(5, &RetryConfig::default())
+}
+
+fn response_with_retry_after(value: &str) -> HttpResponse {
+    HttpResponse {
+        url: "https://www.google.com/search".to_string(),
+        status: 429,
+        headers: vec![("retry-after".to_string(), value.to_string())],
+        body: String::new(),
+    }
+}
+
+#[test]
+fn only_configured_error_kinds_are_retried() {
+    let policy = policy();
+    assert!(policy.is_retryable(&SearchError::Timeout));
+    assert!(policy.is_retryable(&SearchError::NetworkError("reset".to_string())));
+    assert!(policy.is_retryable(&SearchError::RateLimited { retry_after: None }));
+    assert!(!policy.is_retryable(&SearchError::Blocked {
+        reason: "HTTP 403 Forbidden".to_string()
+    }));
+    assert!(!policy.is_retryable(&SearchError::NoResults));
+    assert!(!policy.is_retryable(&SearchError::RecordingMissing {
+        url: "https://www.google.com/search?q=rust".to_string(),
+        dir: "recordings".to_string(),
+    }));
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let policy = policy();
+    let ceilings: Vec<_> = (1..=7).map(|retry| policy.backoff_ceiling(retry)).collect();
+    assert_eq!(
+        ceilings,
+        [500, 1000, 2000, 4000, 8000, 10_000, 10_000].map(Duration::from_millis)
+    );
+    assert_eq!(policy.backoff_ceiling(u32::MAX), Duration::from_secs(10));
+}
+
+#[test]
+fn delays_use_full_jitter_below_the_ceiling() {
+    let policy = policy();
+    let mut rng = StdRng::seed_from_u64(7);
+    let delays: Vec<_> = (0..100)
+        .map(|_| policy.delay(3, &SearchError::Timeout, &mut rng))
+        .collect();
+
+    assert!(delays.iter().all(|delay| *delay <= Duration::from_secs(2)));
+    assert!(delays
+        .iter()
+        .any(|delay| *delay < Duration::from_millis(500)));
+    assert!(delays
+        .iter()
+        .any(|delay| *delay > Duration::from_millis(1500)));
+}
+
+#[test]
+fn retry_after_extends_the_delay() {
+    let mut policy = policy();
+    let mut rng = StdRng::seed_from_u64(7);
+    let error = SearchError::RateLimited {
+        retry_after: Some(Duration::from_secs(5)),
+    };
+    assert!(policy.delay(1, &error, &mut rng) >= Duration::from_secs(5));
+
+    policy.respect_retry_after = false;
+    assert!(policy.delay(1, &error, &mut rng) <= Duration::from_millis(500));
+}
+
+#[test]
+fn retries_stop_at_max_attempts_and_deadline() {
+    let policy = policy();
+    let mut rng = StdRng::seed_from_u64(7);
+    let now = Instant::now();
+
+    assert!(policy
+        .next_delay(1, now, &SearchError::Timeout, &mut rng)
+        .is_some());
+    assert!(policy
+        .next_delay(5, now, &SearchError::Timeout, &mut rng)
+        .is_none());
+    assert!(policy
+        .next_delay(
+            1,
+            now,
+            &SearchError::Blocked {
+                reason: "captcha".to_string()
+            },
+            &mut rng
+        )
+        .is_none());
+
+    let long_wait = SearchError::RateLimited {
+        retry_after: Some(Duration::from_secs(60)),
+    };
+    assert!(policy.next_delay(1, now, &long_wait, &mut rng).is_none());
+}
+
+#[test]
+fn retry_after_header_accepts_seconds_and_dates() {
+    assert_eq!(
+        response_with_retry_after("120").retry_after(),
+        Some(Duration::from_secs(120))
+    );
+
+    let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+    let delay = response_with_retry_after(&date).retry_after().unwrap();
+    assert!(delay > Duration::from_secs(28) && delay <= Duration::from_secs(30));
+
+    assert_eq!(
+        response_with_retry_after("Wed, 21 Oct 2015 07:28:00 GMT").retry_after(),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(response_with_retry_after("soon").retry_after(), None);
+}
@@ -0,0 +1,113 @@
+//! Stale-while-revalidate and refreshing results past the cache
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::SERVER;
+use search_in_terminal::{
+    search::engine::{CacheMode, PageSource, SearchEngine},
+    App,
+};
+
+fn setup() {
+    // Entries expire after a second but stay usable for another minute
+    common::mock_config(
+        "revalidate",
+        "[search]\nprefetch = false\n\n[cache]\ntime_to_live = 1\nstale_window = 60\n",
+    );
+}
+
+/// Poll the app until its background refresh has been applied
+async fn wait_for_refresh(app: &mut App) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.refresh.is_some() {
+        assert!(Instant::now() < deadline, "refresh did not finish");
+        tokio::time::timeout(Duration::from_millis(100), app.next_update())
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn stale_pages_are_shown_and_flagged_for_refresh() {
+    setup();
+    let engine = SearchEngine::favor("google");
+    let first = engine
+        .search_page("stale news", 0, CacheMode::Normal)
+        .await
+        .unwrap();
+    assert_eq!(first.source, PageSource::Network);
+    assert!(!first.revalidate);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let stale = engine
+        .search_page("stale news", 0, CacheMode::Normal)
+        .await
+        .unwrap();
+    assert_eq!(stale.source, PageSource::Stale);
+    assert!(stale.revalidate);
+    assert_eq!(stale.results, first.results);
+    assert_eq!(SERVER.hits("stale news"), 1);
+
+    let refreshed = engine
+        .search_page("stale news", 0, CacheMode::Refresh)
+        .await
+        .unwrap();
+    assert_eq!(refreshed.source, PageSource::Network);
+    assert_eq!(SERVER.hits("stale news"), 2);
+
+    let cached = engine
+        .search_page("stale news", 0, CacheMode::Normal)
+        .await
+        .unwrap();
+    assert_eq!(cached.source, PageSource::Cache);
+    assert!(!cached.revalidate);
+    assert_eq!(SERVER.hits("stale news"), 2);
+}
+
+#[tokio::test]
+async fn app_shows_stale_results_then_swaps_in_fresh_ones() {
+    setup();
+    let engine = SearchEngine::favor("bing");
+    engine.search("rust revalidate", 0).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let mut app = App::new();
+    app.search_engine = engine;
+    app.input = "rust revalidate".to_string();
+    app.perform_search().await.unwrap();
+    assert_eq!(app.search_results.len(), 10);
+    assert!(app.cached);
+    assert!(app.stale);
+    assert!(app.refresh.is_some());
+
+    wait_for_refresh(&mut app).await;
+    assert_eq!(app.search_results.len(), 10);
+    assert!(!app.cached);
+    assert!(!app.stale);
+    assert!(app.refresh.is_none());
+    assert_eq!(app.error_message, None);
+    assert_eq!(SERVER.hits("rust revalidate"), 2);
+}
+
+#[tokio::test]
+async fn manual_refresh_bypasses_fresh_cache_entries() {
+    setup();
+    let mut app = App::new();
+    app.search_engine = SearchEngine::favor("duckduckgo");
+    app.input = "manual refresh".to_string();
+    app.perform_search().await.unwrap();
+    app.perform_search().await.unwrap();
+    assert!(app.cached);
+    assert_eq!(SERVER.hits("manual refresh"), 1);
+
+    app.refresh_search().await.unwrap();
+    assert!(!app.cached);
+    assert_eq!(SERVER.hits("manual refresh"), 2);
+
+    app.offline = true;
+    app.refresh_search().await.unwrap();
+    assert!(app.warning_message.is_some());
+    assert_eq!(SERVER.hits("manual refresh"), 2);
+}
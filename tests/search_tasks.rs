@@ -0,0 +1,233 @@
+//! Searches streaming results from background tasks while the UI stays
+//! responsive
+
+mod common;
+
+use std::time::Duration;
+
+use common::{ctrl, key, SERVER};
+use crossterm::event::KeyCode;
+use futures_util::StreamExt;
+use search_in_terminal::{
+    core::app::{Command, SearchKind},
+    search::engine::{search_stream, CacheMode, SearchEngine},
+    App,
+};
+
+fn setup() {
+    common::mock_config("search_tasks", "");
+}
+
+fn app_with_input(engine: &str, input: &str) -> App {
+    let mut app = App::new();
+    app.search_engine = SearchEngine::favor(engine);
+    app.input = input.to_string();
+    app
+}
+
+#[tokio::test]
+async fn searches_run_in_the_background() {
+    setup();
+    let mut app = app_with_input("google", "background search");
+    app.start_search(app.cache_mode());
+    assert!(app.is_loading);
+    assert!(!app.input_mode);
+    assert_eq!(
+        app.search.as_ref().map(|task| task.kind),
+        Some(SearchKind::FirstPage)
+    );
+    assert!(app.search_results.is_empty());
+
+    // Keys are still handled while the search runs
+    assert_eq!(app.handle_key(key(KeyCode::Char('j'))), Command::None);
+
+    app.wait_for_search().await;
+    assert!(!app.is_loading);
+    assert!(app.search.is_none());
+    assert_eq!(app.search_results.len(), 10);
+    assert_eq!(app.search_results[0].title, "background search result 1");
+}
+
+#[tokio::test]
+async fn a_new_search_supersedes_the_running_one() {
+    setup();
+    // The first query hangs, so only the second can answer
+    let mut app = app_with_input("bing", "timeout superseded");
+    app.start_search(app.cache_mode());
+    app.input = "superseding".to_string();
+    app.start_search(app.cache_mode());
+
+    app.wait_for_search().await;
+    assert_eq!(app.search_results.len(), 10);
+    assert_eq!(app.search_results[0].title, "superseding result 1");
+    assert_eq!(app.error_message, None);
+}
+
+#[tokio::test]
+async fn paging_past_the_last_page_loads_the_next_one() {
+    setup();
+    let mut app = app_with_input("duckduckgo", "paging ahead");
+    app.perform_search().await.unwrap();
+    assert_eq!((app.page, app.total_pages), (0, 1));
+
+    app.change_page(1);
+    assert!(app.is_loading);
+    assert_eq!(
+        app.search.as_ref().map(|task| task.kind),
+        Some(SearchKind::NextPage)
+    );
+    // Another page request while loading doesn't start a second search
+    app.change_page(1);
+
+    app.wait_for_search().await;
+    assert_eq!(app.search_results.len(), 20);
+    assert_eq!((app.page, app.total_pages), (1, 2));
+    assert_eq!(app.start, 10);
+    assert_eq!(SERVER.hits("paging ahead"), 2);
+}
+
+#[tokio::test]
+async fn esc_cancels_the_running_search() {
+    setup();
+    let mut app = app_with_input("google", "timeout cancelled");
+    app.start_search(app.cache_mode());
+    assert_eq!(app.handle_key(key(KeyCode::Esc)), Command::None);
+
+    assert!(!app.is_loading);
+    assert!(app.search.is_none());
+    assert_eq!(app.warning_message.as_deref(), Some("Search cancelled"));
+    assert!(!app.poll_updates());
+}
+
+#[tokio::test]
+async fn ctrl_c_cancels_the_running_search_while_typing() {
+    setup();
+    let mut app = app_with_input("bing", "timeout while typing");
+    app.start_search(app.cache_mode());
+    app.input_mode = true;
+    let ctrl_c = ctrl('c');
+    assert_eq!(app.handle_key(ctrl_c), Command::None);
+
+    assert!(!app.is_loading);
+    assert_eq!(app.input, "timeout while typing");
+    // Esc only leaves input mode
+    app.handle_key(key(KeyCode::Esc));
+    assert!(!app.input_mode);
+}
+
+#[tokio::test]
+async fn cancelling_a_page_load_keeps_the_loaded_results() {
+    setup();
+    let mut app = app_with_input("duckduckgo", "page load cancelled");
+    app.perform_search().await.unwrap();
+    app.change_page(1);
+    assert_eq!(app.start, 10);
+
+    app.cancel_search();
+    assert_eq!(app.start, 0);
+    assert_eq!(app.search_results.len(), 10);
+    assert_eq!(app.page, 0);
+}
+
+#[tokio::test]
+async fn streams_yield_a_batch_per_engine() {
+    setup();
+    let batches: Vec<_> = search_stream(SearchEngine::all(), "streamed", 0, CacheMode::Normal)
+        .collect()
+        .await;
+
+    let mut engines: Vec<_> = batches.iter().map(|batch| batch.engine.as_str()).collect();
+    engines.sort_unstable();
+    assert_eq!(engines, ["Bing", "DuckDuckGo", "Google"]);
+    for batch in &batches {
+        assert_eq!(batch.start, 0);
+        assert_eq!(batch.page.as_ref().unwrap().results.len(), 10);
+    }
+}
+
+#[tokio::test]
+async fn all_engines_merge_their_results() {
+    setup();
+    let mut app = app_with_input("google", "every engine");
+    app.handle_key(key(KeyCode::Esc));
+    assert_eq!(app.handle_key(key(KeyCode::Char('a'))), Command::Search);
+    assert!(app.all_engines);
+
+    app.perform_search().await.unwrap();
+    // The mock engines return the same pages, so duplicates are dropped
+    assert_eq!(app.search_results.len(), 10);
+    assert_eq!(app.error_message, None);
+    assert_eq!(app.warning_message, None);
+    assert_eq!(SERVER.hits("every engine"), 3);
+
+    // Picking an engine leaves all-engines mode
+    assert_eq!(app.handle_key(key(KeyCode::Char('e'))), Command::Search);
+    assert!(!app.all_engines);
+    assert_eq!(app.search_engine.as_str(), "Google");
+}
+
+#[tokio::test]
+async fn failing_engines_do_not_hide_the_others() {
+    setup();
+    SearchEngine::favor("bing")
+        .search("partly cached", 0)
+        .await
+        .unwrap();
+
+    // Offline, only Bing has the query cached
+    let mut app = app_with_input("google", "partly cached");
+    app.all_engines = true;
+    app.offline = true;
+    app.perform_search().await.unwrap();
+
+    assert_eq!(app.search_results.len(), 10);
+    assert!(app.cached);
+    assert_eq!(app.error_message, None);
+    let warning = app.warning_message.unwrap();
+    assert!(warning.starts_with("Some engines failed: "), "{}", warning);
+    assert!(warning.contains("Google: No cached results"), "{}", warning);
+    assert!(
+        warning.contains("DuckDuckGo: No cached results"),
+        "{}",
+        warning
+    );
+}
+
+#[tokio::test]
+async fn the_next_page_is_prefetched_from_the_last_page() {
+    setup();
+    let mut app = app_with_input("google", "prefetched");
+    app.perform_search().await.unwrap();
+    let prefetch = app.prefetch.as_ref().unwrap();
+    assert_eq!((prefetch.start, prefetch.kind), (10, SearchKind::NextPage));
+
+    // The prefetch reports its page and then that it's done
+    for _ in 0..2 {
+        tokio::time::timeout(Duration::from_secs(5), app.next_update())
+            .await
+            .unwrap();
+    }
+    assert_eq!(SERVER.hits("prefetched"), 2);
+    assert_eq!(app.search_results.len(), 10);
+
+    // Paging on applies the prefetched page at once and prefetches the next
+    app.change_page(1);
+    assert!(!app.is_loading);
+    assert_eq!(app.search_results.len(), 20);
+    assert_eq!((app.page, app.start), (1, 10));
+    assert_eq!(app.prefetch.as_ref().map(|task| task.start), Some(20));
+}
+
+#[tokio::test]
+async fn a_new_search_drops_the_prefetch() {
+    setup();
+    let mut app = app_with_input("bing", "dropped prefetch");
+    app.perform_search().await.unwrap();
+    assert!(app.prefetch.is_some());
+
+    app.input = "another query".to_string();
+    app.start_search(app.cache_mode());
+    assert!(app.prefetch.is_none());
+    app.wait_for_search().await;
+    assert_eq!(app.search_results[0].title, "another query result 1");
+}
@@ -0,0 +1,264 @@
+//! Query autocomplete from the engines' suggest endpoints and the history
+
+mod common;
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use common::{ctrl, key, SERVER};
+use crossterm::event::KeyCode;
+use search_in_terminal::{
+    core::{
+        app::{Command, Suggestion, SuggestionSource},
+        history::History,
+    },
+    search::engine::{parse_suggestions, SearchEngine},
+    App, SearchError,
+};
+
+fn history_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("suggest")
+        .join(format!("{}.jsonl", name))
+}
+
+fn setup() {
+    common::mock_config(
+        "suggest",
+        &format!(
+            "[search]\nprefetch = false\n\n\
+             [history]\npath = {:?}\n\n\
+             [suggest]\ndebounce = 0\nmax_items = 5\n",
+            history_path("app").display().to_string()
+        ),
+    );
+}
+
+/// An app in input mode whose history holds `queries`, oldest first
+fn app(name: &str, queries: &[&str]) -> App {
+    setup();
+    let path = history_path(name);
+    let _ = fs::remove_file(&path);
+    let mut history = History::load(path, 100, true);
+    for query in queries {
+        history.add(query, "Google").unwrap();
+    }
+
+    let mut app = App::new();
+    app.search_engine = SearchEngine::favor("google");
+    app.history = history;
+    app
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_key(key(KeyCode::Char(c)));
+    }
+}
+
+/// Polls the app until the engine's suggestions for the input have arrived
+async fn wait_for_suggestions(app: &mut App) {
+    // The mock engine's last suggestion names the engine
+    let last = format!("{} google", app.input);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            app.poll_updates();
+            if app.suggestions.iter().any(|suggestion| {
+                suggestion.source == SuggestionSource::Engine && suggestion.text == last
+            }) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("No suggestions from the engine");
+}
+
+fn texts(suggestions: &[Suggestion]) -> Vec<&str> {
+    suggestions
+        .iter()
+        .map(|suggestion| suggestion.text.as_str())
+        .collect()
+}
+
+#[test]
+fn opensearch_responses_are_parsed() {
+    assert_eq!(
+        parse_suggestions(r#"["rust",["rust book","rust lang"],[],{}]"#).unwrap(),
+        ["rust book", "rust lang"]
+    );
+    assert_eq!(
+        parse_suggestions(r#"["rust",[]]"#).unwrap(),
+        Vec::<String>::new()
+    );
+
+    for body in ["<html>", r#"{"rust": []}"#] {
+        let error = parse_suggestions(body).unwrap_err();
+        assert!(
+            matches!(error.downcast_ref(), Some(SearchError::InvalidResponse(_))),
+            "{}",
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn every_engine_suggests_and_answers_are_cached() {
+    setup();
+    for engine in SearchEngine::all() {
+        let name = engine.as_str().to_lowercase();
+        let query = format!("autocomplete {}", name);
+        let suggestions = engine.suggest(&query).await.unwrap();
+        assert_eq!(
+            suggestions,
+            [
+                format!("{} tutorial", query),
+                format!("{} book", query),
+                format!("{} {}", query, name),
+            ]
+        );
+
+        engine.suggest(&query).await.unwrap();
+        assert_eq!(SERVER.hits(&format!("suggest:{}", query)), 1);
+    }
+}
+
+#[tokio::test]
+async fn history_comes_first_and_engine_suggestions_follow() {
+    let mut app = app("merge", &["rust tutorial", "rusty", "go"]);
+    type_text(&mut app, "rust");
+    assert_eq!(texts(&app.suggestions), ["rusty", "rust tutorial"]);
+    assert_eq!(app.suggestions[0].source, SuggestionSource::History);
+
+    wait_for_suggestions(&mut app).await;
+    // The engine's "rust tutorial" is already listed from the history
+    assert_eq!(
+        texts(&app.suggestions),
+        ["rusty", "rust tutorial", "rust book", "rust google"]
+    );
+
+    // Typing on keeps the matching suggestions until the engine answers again
+    type_text(&mut app, " b");
+    assert_eq!(texts(&app.suggestions), ["rust book"]);
+    wait_for_suggestions(&mut app).await;
+    assert_eq!(
+        texts(&app.suggestions),
+        ["rust b tutorial", "rust b book", "rust b google"]
+    );
+}
+
+#[tokio::test]
+async fn suggestions_are_selected_with_tab_and_arrows() {
+    let mut app = app("keys", &["rust lang"]);
+    type_text(&mut app, "rust");
+    wait_for_suggestions(&mut app).await;
+    assert_eq!(app.selected_suggestion, None);
+
+    app.handle_key(key(KeyCode::Tab));
+    assert_eq!(app.selected_suggestion, Some(0));
+    app.handle_key(key(KeyCode::Down));
+    app.handle_key(key(KeyCode::Tab));
+    assert_eq!(app.selected_suggestion, Some(2));
+    app.handle_key(key(KeyCode::BackTab));
+    assert_eq!(app.selected_suggestion, Some(1));
+    app.handle_key(key(KeyCode::Up));
+    app.handle_key(key(KeyCode::Up));
+    assert_eq!(app.selected_suggestion, None);
+    assert_eq!(app.input, "rust");
+
+    // Up above the list recalls the history instead
+    app.handle_key(key(KeyCode::Up));
+    assert_eq!(app.input, "rust lang");
+    assert!(app.suggestions.is_empty());
+}
+
+#[tokio::test]
+async fn enter_searches_for_the_selected_suggestion() {
+    let mut app = app("enter", &[]);
+    type_text(&mut app, "tokio");
+    wait_for_suggestions(&mut app).await;
+
+    app.handle_key(key(KeyCode::Down));
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Command::Search);
+    assert_eq!(app.input, "tokio book");
+    assert_eq!(app.cursor, "tokio book".len());
+    assert!(app.suggestions.is_empty());
+    assert_eq!(app.history.get(0).unwrap().query, "tokio book");
+}
+
+#[tokio::test]
+async fn esc_hides_the_suggestions_and_late_answers_are_dropped() {
+    let mut app = app("esc", &["serde json"]);
+    type_text(&mut app, "serde");
+    wait_for_suggestions(&mut app).await;
+    app.handle_key(key(KeyCode::Esc));
+    assert!(app.suggestions.is_empty());
+    assert!(app.input_mode);
+
+    // Asked, then hidden before the answer is applied
+    type_text(&mut app, " ");
+    assert_eq!(texts(&app.suggestions), ["serde json"]);
+    app.poll_updates();
+    app.handle_key(key(KeyCode::Esc));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    app.poll_updates();
+    assert!(app.suggestions.is_empty());
+
+    // Without suggestions Esc leaves input mode, dropping pending requests
+    app.handle_key(ctrl('u'));
+    type_text(&mut app, "tokio");
+    assert!(app.suggestions.is_empty());
+    app.poll_updates();
+    app.handle_key(key(KeyCode::Esc));
+    assert!(!app.input_mode);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    app.poll_updates();
+    assert!(app.suggestions.is_empty());
+}
+
+#[tokio::test]
+async fn offline_mode_suggests_only_from_the_history() {
+    let mut app = app("offline", &["offline docs"]);
+    app.offline = true;
+    type_text(&mut app, "offline");
+    assert_eq!(texts(&app.suggestions), ["offline docs"]);
+
+    // Past the debounce, then long enough for a request to be answered
+    for _ in 0..2 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        app.poll_updates();
+    }
+    assert_eq!(texts(&app.suggestions), ["offline docs"]);
+    assert_eq!(SERVER.hits("suggest:offline"), 0);
+}
+
+#[tokio::test]
+async fn incognito_mode_suggests_only_from_the_history() {
+    let mut app = app("incognito", &["incognito docs"]);
+    app.incognito = true;
+    type_text(&mut app, "incognito");
+    assert_eq!(texts(&app.suggestions), ["incognito docs"]);
+
+    // Past the debounce, then long enough for a request to be answered
+    for _ in 0..2 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        app.poll_updates();
+    }
+    assert_eq!(texts(&app.suggestions), ["incognito docs"]);
+    assert_eq!(SERVER.hits("suggest:incognito"), 0);
+}
+
+#[tokio::test]
+async fn snapshot_suggestions() {
+    let mut app = app("snapshot", &["rust async"]);
+    type_text(&mut app, "rust");
+    wait_for_suggestions(&mut app).await;
+    app.handle_key(key(KeyCode::Tab));
+    app.handle_key(key(KeyCode::Tab));
+
+    insta::assert_snapshot!(
+        "suggestions_80x24",
+        common::draw(&mut app, 80, 24).backend()
+    );
+}
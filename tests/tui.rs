@@ -0,0 +1,235 @@
+//! Key handling and rendering tests for the terminal UI
+//!
+//! Screens are compared against insta snapshots in `tests/snapshots`; review
+//! intentional layout changes with `cargo insta review`.
+
+mod common;
+
+use common::{ctrl, key};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use search_in_terminal::{core::app::Command, App, SearchResult};
+
+fn setup() {
+    let history = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("tui/history.jsonl");
+    let config = format!(
+        "[search]\nprefetch = false\n\n[engine]\nfavor = \"google\"\n\n\
+         [history]\npath = {:?}\n\n[suggest]\nenabled = false\n",
+        history.display().to_string()
+    );
+    common::use_config("tui", &config);
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        assert_eq!(app.handle_key(key(KeyCode::Char(c))), Command::None);
+    }
+}
+
+/// An app in results mode holding `count` fake results
+fn app_with_results(count: usize) -> App {
+    let mut app = App::new();
+    app.input = "rust programming".to_string();
+    app.input_mode = false;
+    app.search_results = (1..=count)
+        .map(|i| SearchResult {
+            title: format!("Result title {}", i),
+            url: format!("https://example.com/{}", i),
+            description: format!("Description of result {}", i),
+        })
+        .collect();
+    app.total_pages();
+    app
+}
+
+#[test]
+fn typing_edits_the_input() {
+    setup();
+    let mut app = App::new();
+    assert!(app.input_mode);
+
+    type_text(&mut app, "rust lang");
+    assert_eq!(app.input, "rust lang");
+
+    app.handle_key(key(KeyCode::Backspace));
+    app.handle_key(ctrl('h'));
+    assert_eq!(app.input, "rust la");
+
+    app.handle_key(ctrl('u'));
+    assert_eq!(app.input, "");
+}
+
+#[tokio::test]
+async fn input_mode_keys_do_not_trigger_commands() {
+    setup();
+    let mut app = App::new();
+
+    type_text(&mut app, "qhlj");
+    assert_eq!(app.input, "qhlj");
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Command::Search);
+
+    app.handle_key(key(KeyCode::Esc));
+    assert!(!app.input_mode);
+}
+
+#[test]
+fn results_mode_keys_map_to_commands() {
+    setup();
+    let mut app = app_with_results(15);
+
+    assert_eq!(
+        app.handle_key(key(KeyCode::Char('l'))),
+        Command::ChangePage(1)
+    );
+    assert_eq!(app.handle_key(key(KeyCode::Right)), Command::ChangePage(1));
+    assert_eq!(
+        app.handle_key(key(KeyCode::Char('h'))),
+        Command::ChangePage(-1)
+    );
+    assert_eq!(app.handle_key(key(KeyCode::Left)), Command::ChangePage(-1));
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Command::OpenUrl);
+    assert_eq!(app.handle_key(key(KeyCode::Char('R'))), Command::Refresh);
+    assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Command::Quit);
+
+    app.handle_key(key(KeyCode::Char('j')));
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.list_state.selected(), Some(2));
+    app.handle_key(key(KeyCode::Char('k')));
+    assert_eq!(app.list_state.selected(), Some(1));
+
+    app.handle_key(key(KeyCode::Char('i')));
+    assert!(app.input_mode);
+}
+
+#[test]
+fn switching_engine_searches_again_only_with_a_query() {
+    setup();
+    let mut app = app_with_results(3);
+    assert_eq!(app.handle_key(key(KeyCode::Char('e'))), Command::Search);
+    assert_eq!(app.search_engine.as_str(), "Bing");
+
+    app.handle_key(key(KeyCode::Char('r')));
+    assert!(app.search_results.is_empty());
+    assert!(app.input_mode);
+
+    app.handle_key(key(KeyCode::Esc));
+    assert_eq!(app.handle_key(key(KeyCode::Char('e'))), Command::None);
+    assert_eq!(app.search_engine.as_str(), "DuckDuckGo");
+}
+
+#[test]
+fn offline_mode_is_toggled_in_results_mode() {
+    setup();
+    let mut app = app_with_results(3);
+    assert_eq!(app.handle_key(key(KeyCode::Char('o'))), Command::None);
+    assert!(app.offline);
+    app.handle_key(key(KeyCode::Char('o')));
+    assert!(!app.offline);
+
+    app.handle_key(key(KeyCode::Char('i')));
+    type_text(&mut app, "o");
+    assert!(!app.offline);
+}
+
+#[test]
+fn snapshot_input_mode() {
+    setup();
+    let mut app = App::new();
+    insta::assert_snapshot!(
+        "input_empty_80x24",
+        common::draw(&mut app, 80, 24).backend()
+    );
+
+    type_text(&mut app, "rust programming");
+    insta::assert_snapshot!(
+        "input_typing_80x24",
+        common::draw(&mut app, 80, 24).backend()
+    );
+}
+
+#[test]
+fn terminal_cursor_follows_the_input_cursor() {
+    setup();
+    let mut app = App::new();
+    type_text(&mut app, "rust programming");
+    common::draw(&mut app, 80, 24)
+        .backend_mut()
+        .assert_cursor_position((17, 1));
+
+    app.handle_key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::ALT));
+    common::draw(&mut app, 80, 24)
+        .backend_mut()
+        .assert_cursor_position((6, 1));
+
+    // Past the edge of the box the input scrolls instead
+    type_text(&mut app, &"x".repeat(100));
+    let mut terminal = common::draw(&mut app, 40, 12);
+    terminal.backend_mut().assert_cursor_position((38, 1));
+    insta::assert_snapshot!("input_scrolled_40x12", terminal.backend());
+}
+
+#[test]
+fn snapshot_loading() {
+    setup();
+    let mut app = App::new();
+    type_text(&mut app, "rust programming");
+    app.is_loading = true;
+    insta::assert_snapshot!("loading_80x24", common::draw(&mut app, 80, 24).backend());
+}
+
+#[test]
+fn snapshot_results() {
+    setup();
+    let mut app = app_with_results(3);
+    insta::assert_snapshot!("results_80x24", common::draw(&mut app, 80, 24).backend());
+    insta::assert_snapshot!("results_120x40", common::draw(&mut app, 120, 40).backend());
+    insta::assert_snapshot!("results_40x12", common::draw(&mut app, 40, 12).backend());
+}
+
+#[test]
+fn snapshot_pagination() {
+    setup();
+    let mut app = app_with_results(15);
+    app.change_page(1);
+    assert_eq!(app.page, 1);
+    insta::assert_snapshot!(
+        "pagination_100x30",
+        common::draw(&mut app, 100, 30).backend()
+    );
+}
+
+#[test]
+fn snapshot_offline_stale() {
+    setup();
+    let mut app = app_with_results(3);
+    app.offline = true;
+    app.stale = true;
+    insta::assert_snapshot!(
+        "offline_stale_120x40",
+        common::draw(&mut app, 120, 40).backend()
+    );
+}
+
+#[test]
+fn snapshot_cached() {
+    setup();
+    let mut app = app_with_results(3);
+    app.cached = true;
+    insta::assert_snapshot!("cached_120x40", common::draw(&mut app, 120, 40).backend());
+}
+
+#[test]
+fn snapshot_warning() {
+    setup();
+    let mut app = app_with_results(3);
+    app.warning_message = Some("No more results found".to_string());
+    insta::assert_snapshot!("warning_80x24", common::draw(&mut app, 80, 24).backend());
+}
+
+#[test]
+fn snapshot_error() {
+    setup();
+    let mut app = App::new();
+    app.input_mode = false;
+    app.error_message = Some("Search failed: Rate limited by search engine".to_string());
+    insta::assert_snapshot!("error_80x24", common::draw(&mut app, 80, 24).backend());
+}